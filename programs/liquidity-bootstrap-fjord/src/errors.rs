@@ -59,4 +59,97 @@ pub enum ErrorCode {
 
   #[msg("Pool is closed")]
   PoolIsClosed,
-}
\ No newline at end of file
+
+  #[msg("Invalid Referrer")]
+  InvalidReferrer,
+
+  #[msg("Claiming disallowed")]
+  ClaimingDisallowed,
+
+  #[msg("No Referrer Fees To Claim")]
+  NoReferrerFeesToClaim,
+
+  #[msg("Selling disallowed")]
+  SellingDisallowed,
+
+  #[msg("Trading not started")]
+  TradingNotStarted,
+
+  #[msg("Trading ended")]
+  TradingEnded,
+
+  #[msg("Unsupported mint extension")]
+  UnsupportedMintExtension,
+
+  #[msg("Invalid Weight Curve")]
+  InvalidWeightCurve,
+
+  #[msg("Settings update disallowed")]
+  SettingsUpdateDisallowed,
+
+  #[msg("Invalid Authority")]
+  InvalidAuthority,
+
+  #[msg("No pending ownership transfer")]
+  NoPendingOwnershipTransfer,
+
+  #[msg("Factory is paused")]
+  FactoryIsPaused,
+
+  #[msg("Invalid allowlist proof")]
+  InvalidAllowlistProof,
+
+  #[msg("Allocation exceeded")]
+  AllocationExceeded,
+
+  #[msg("Invalid Purchase Limits")]
+  InvalidPurchaseLimits,
+
+  #[msg("Max Shares Per User Exceeded")]
+  MaxSharesPerUserExceeded,
+
+  #[msg("Max Assets Per User Exceeded")]
+  MaxAssetsPerUserExceeded,
+
+  #[msg("Min Assets Per Buy Not Met")]
+  MinAssetsPerBuyNotMet,
+
+  #[msg("Insufficient Shares")]
+  InsufficientShares,
+
+  #[msg("Arithmetic overflow")]
+  Overflow,
+
+  #[msg("Arithmetic underflow")]
+  Underflow,
+
+  #[msg("Division by zero")]
+  DivisionByZero,
+
+  #[msg("Insufficient reserve")]
+  InsufficientReserve,
+
+  #[msg("Invalid Max Share Price")]
+  InvalidMaxSharePrice,
+
+  #[msg("Amount out too large")]
+  AmountOutTooLarge,
+
+  #[msg("Amount in too large")]
+  AmountInTooLarge,
+
+  #[msg("Invalid Max Percentage")]
+  InvalidMaxPercentage,
+
+  #[msg("Invalid Vesting Schedule")]
+  InvalidVestingSchedule,
+
+  #[msg("Redeem Amount Exceeds Claimable")]
+  RedeemAmountExceedsClaimable,
+
+  #[msg("Invalid Redeem Accounts")]
+  InvalidRedeemAccounts,
+
+  #[msg("Invalid Recipient Share Vault")]
+  InvalidRecipientShareVault,
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+  #[account(
+    mut,
+    constraint = lbp_factory_setting.pending_authority == pending_authority.key() @ ErrorCode::Unauthorized,
+  )]
+  pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
+  pub pending_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptOwnership>) -> Result<()> {
+  let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
+
+  let previous_authority = lbp_factory_setting.authority;
+  lbp_factory_setting.authority = lbp_factory_setting.pending_authority;
+  lbp_factory_setting.pending_authority = Pubkey::default();
+
+  emit!(OwnershipTransferred {
+    previous_authority,
+    new_authority: lbp_factory_setting.authority,
+  });
+
+  Ok(())
+}
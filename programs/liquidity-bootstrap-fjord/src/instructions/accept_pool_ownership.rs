@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+
+#[derive(Accounts)]
+pub struct AcceptPoolOwnership<'info> {
+
+  #[account(
+    mut,
+    constraint = pool.pending_owner == pending_owner.key() @ ErrorCode::Unauthorized,
+  )]
+  pub pool: Box<Account<'info, Pool>>,
+
+  pub pending_owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptPoolOwnership>) -> Result<()> {
+  let pool = &mut ctx.accounts.pool;
+
+  let previous_owner = pool.owner;
+  pool.owner = pool.pending_owner;
+  pool.pending_owner = Pubkey::default();
+
+  emit!(PoolOwnershipTransferred {
+    pool: pool.key(),
+    previous_owner,
+    new_owner: pool.owner,
+  });
+
+  Ok(())
+}
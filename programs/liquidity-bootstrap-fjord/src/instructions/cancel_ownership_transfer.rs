@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+
+#[derive(Accounts)]
+pub struct CancelOwnershipTransfer<'info> {
+  #[account(mut, has_one = authority)]
+  pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
+  pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+  let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
+
+  if lbp_factory_setting.pending_authority == Pubkey::default() {
+    return err!(ErrorCode::NoPendingOwnershipTransfer);
+  }
+
+  emit!(OwnershipTransferCancelled {
+    authority: lbp_factory_setting.authority,
+    cancelled_authority: lbp_factory_setting.pending_authority,
+  });
+
+  lbp_factory_setting.pending_authority = Pubkey::default();
+
+  Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[derive(Accounts)]
+pub struct ClaimReferrerFees<'info> {
+
+  pub referrer: Signer<'info>,
+
+  pub pool: Box<Account<'info, Pool>>,
+
+  #[account(
+    mut,
+    constraint = asset_vault.mint == pool.settings.asset,
+    constraint = asset_vault.owner == pool.asset_vault_authority,
+  )]
+  pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+  /// CHECK: This is not dangerous because we don't read or write from this account
+  #[account(
+    seeds = [
+      b"asset".as_ref(),
+      pool.to_account_info().key.as_ref()
+    ],
+    bump = pool.asset_vault_nonce,
+  )]
+  pub asset_vault_authority: AccountInfo<'info>,
+
+  #[account(
+    mut,
+    seeds = [b"referrer_stats".as_ref(), pool.key().as_ref(), referrer.key().as_ref()],
+    bump = referrer_stats.bump,
+  )]
+  pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+
+  #[account(
+    mut,
+    constraint = referrer_asset_vault.mint == pool.settings.asset,
+    constraint = referrer_asset_vault.owner == referrer.key(),
+  )]
+  pub referrer_asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+  #[account(
+    constraint = asset_mint.key() == pool.settings.asset,
+  )]
+  pub asset_mint: InterfaceAccount<'info, Mint>,
+
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler(ctx: Context<ClaimReferrerFees>) -> Result<()> {
+  // Referral fees stay in the asset vault until the pool is closed
+  if !ctx.accounts.pool.closed {
+    return err!(ErrorCode::ClaimingDisallowed);
+  }
+
+  let referrer_stats = &mut ctx.accounts.referrer_stats;
+  let claimable = referrer_stats.accrued.safe_sub(referrer_stats.claimed)?;
+
+  if claimable == 0 {
+    return err!(ErrorCode::NoReferrerFeesToClaim);
+  }
+
+  let seeds = &[
+    b"asset".as_ref(),
+    ctx.accounts.pool.to_account_info().key.as_ref(),
+    &[ctx.accounts.pool.asset_vault_nonce],
+  ];
+  let signer = &[&seeds[..]];
+
+  token_interface::transfer_checked(
+    CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.asset_vault.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
+            to: ctx.accounts.referrer_asset_vault.to_account_info(),
+            authority: ctx.accounts.asset_vault_authority.to_account_info(),
+        },
+        signer,
+    ),
+    claimable,
+    ctx.accounts.asset_mint.decimals,
+  )?;
+
+  referrer_stats.claimed = referrer_stats.claimed.safe_add(claimable)?;
+
+  emit!(ReferrerFeesClaimed {
+    referrer: ctx.accounts.referrer.key(),
+    assets: claimable,
+  });
+
+  Ok(())
+}
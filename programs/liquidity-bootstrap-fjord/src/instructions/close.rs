@@ -2,11 +2,13 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::utils::*;
 use crate::errors::ErrorCode;
-use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 #[derive(Accounts)]
 pub struct Close<'info> {
 
+  pub closer: Signer<'info>,
+
   #[account(mut)]
   pub pool: Box<Account<'info, Pool>>,
 
@@ -15,7 +17,7 @@ pub struct Close<'info> {
     constraint = asset_vault.mint == pool.settings.asset,
     constraint = asset_vault.owner == pool.asset_vault_authority,
   )]
-  pub asset_vault: Account<'info, TokenAccount>,
+  pub asset_vault: InterfaceAccount<'info, TokenAccount>,
 
   /// CHECK: This is not dangerous because we don't read or write from this account
   #[account(
@@ -32,7 +34,7 @@ pub struct Close<'info> {
     constraint = share_vault.mint == pool.settings.share,
     constraint = share_vault.owner == pool.share_vault_authority,
   )]
-  pub share_vault: Account<'info, TokenAccount>,
+  pub share_vault: InterfaceAccount<'info, TokenAccount>,
 
   /// CHECK: This is not dangerous because we don't read or write from this account
   #[account(
@@ -48,26 +50,26 @@ pub struct Close<'info> {
     mut,
     constraint = pool_owner_asset_vault.owner == pool.owner
   )]
-  pub pool_owner_asset_vault: Account<'info, TokenAccount>,
+  pub pool_owner_asset_vault: InterfaceAccount<'info, TokenAccount>,
   
   #[account(
     mut,
     constraint = pool_owner_share_vault.owner == pool.owner
   )]
-  pub pool_owner_share_vault: Account<'info, TokenAccount>,
+  pub pool_owner_share_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
     constraint = fee_recipient_asset_vault.owner == lbp_factory_setting.fee_recipient
   )]
-  pub fee_recipient_asset_vault: Account<'info, TokenAccount>,
+  pub fee_recipient_asset_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
     constraint = fee_recipient_share_vault.mint == pool.settings.share,
     constraint = fee_recipient_share_vault.owner == lbp_factory_setting.fee_recipient
   )]
-  pub fee_recipient_share_vault: Account<'info, TokenAccount>,
+  pub fee_recipient_share_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
@@ -75,7 +77,20 @@ pub struct Close<'info> {
   )]
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 
-  pub token_program: Program<'info, Token>,
+  #[account(
+    constraint = asset_mint.key() == pool.settings.asset,
+  )]
+  pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(
+    constraint = share_mint.key() == pool.settings.share,
+  )]
+  pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+  #[account(address = *asset_mint.to_account_info().owner)]
+  pub asset_token_program: Interface<'info, TokenInterface>,
+  #[account(address = *share_mint.to_account_info().owner)]
+  pub share_token_program: Interface<'info, TokenInterface>,
   pub system_program: Program<'info, System>,
 }
 
@@ -93,14 +108,38 @@ pub fn handler(ctx: Context<Close>) -> Result<()> {
     Err(_) => return err!(ErrorCode::ClockError),
   };
 
-  // if (unix_timestamp as i128) < (ctx.accounts.pool.settings.sale_end as i128) {
-  //   return err!(ErrorCode::ClosingDisallowed);
-  // }
+  let sale_end = ctx.accounts.pool.settings.sale_end;
+  if (unix_timestamp as i128) < (sale_end as i128) {
+    return err!(ErrorCode::ClosingDisallowed);
+  }
+
+  // The pool owner and factory authority can close as soon as the sale ends,
+  // anyone else has to wait for the grace period to pass
+  let closer = ctx.accounts.closer.key();
+  let is_owner = ctx.accounts.pool.has_role(&closer, PoolRole::Owner);
+  let is_factory_authority = closer == lbp_factory_setting.authority;
+  let grace_period_end = sale_end as i128 + lbp_factory_setting.close_grace_period as i128;
+  let grace_period_passed = (unix_timestamp as i128) >= grace_period_end;
+  if !is_owner && !is_factory_authority && !grace_period_passed {
+    return err!(ErrorCode::Unauthorized);
+  }
+
+  // A pause that extends the sale only moves sale_end on unpause, so the owner can't skip the
+  // extension by closing while paused. The factory authority and closers after the grace period
+  // ignore it, an owner who never unpauses can't lock redeems and referrer fees in the pool.
+  if !is_factory_authority && !grace_period_passed {
+    let pending_extension = ctx.accounts.pool.pending_sale_extension(unix_timestamp as u64);
+    if (unix_timestamp as i128) < (sale_end.safe_add(pending_extension)? as i128) {
+      return err!(ErrorCode::ClosingDisallowed);
+    }
+  }
 
-  // 1. Calculation
-  let total_assets = assets - ctx.accounts.pool.total_swap_fees_asset;
-  let platform_fees = (total_assets * lbp_factory_setting.platform_fee) / 1_000_000_000;
-  let total_assets_minus_fees = total_assets - platform_fees;
+  // 1. Calculation, referral fees stay in the vault until claimed by referrers
+  let total_assets = assets
+    .checked_sub(ctx.accounts.pool.total_swap_fees_asset.safe_add(ctx.accounts.pool.total_referred)?)
+    .ok_or(error!(ErrorCode::InsufficientReserve))?;
+  let platform_fees = apply_rate(total_assets, lbp_factory_setting.platform_fee)?;
+  let total_assets_minus_fees = total_assets.safe_sub(platform_fees)?;
 
   // 2. Transfer fees to fee recipient
   let asset_seeds = &[
@@ -117,61 +156,71 @@ pub fn handler(ctx: Context<Close>) -> Result<()> {
     ];
   let share_signer = &[&share_seeds[..]];
 
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {  
+        ctx.accounts.asset_token_program.to_account_info(),
+        TransferChecked {  
             from: ctx.accounts.asset_vault.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
             to: ctx.accounts.fee_recipient_asset_vault.to_account_info(),
             authority: ctx.accounts.asset_vault_authority.to_account_info(),
         },
         asset_signer,
     ),
-    platform_fees + ctx.accounts.pool.total_swap_fees_asset,
+    platform_fees.safe_add(ctx.accounts.pool.total_swap_fees_asset)?,
+    ctx.accounts.asset_mint.decimals,
   )?;
 
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {  
+        ctx.accounts.share_token_program.to_account_info(),
+        TransferChecked {  
             from: ctx.accounts.share_vault.to_account_info(),
+            mint: ctx.accounts.share_mint.to_account_info(),
             to: ctx.accounts.fee_recipient_share_vault.to_account_info(), // fee reciever
             authority: ctx.accounts.share_vault_authority.to_account_info(),
         },
         share_signer
     ),
-    ctx.accounts.pool.total_swap_fees_share 
+    ctx.accounts.pool.total_swap_fees_share,
+    ctx.accounts.share_mint.decimals,
   )?;
 
   // 3. Transfer assets and unsold shares to pool owner
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {  
+        ctx.accounts.asset_token_program.to_account_info(),
+        TransferChecked {  
             from: ctx.accounts.asset_vault.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
             to: ctx.accounts.pool_owner_asset_vault.to_account_info(), // fee reciever
             authority: ctx.accounts.asset_vault_authority.to_account_info(),
         },
         asset_signer
     ),
     total_assets_minus_fees,
+    ctx.accounts.asset_mint.decimals,
   )?;
 
-  let unsold_shares = shares - ctx.accounts.pool.total_purchased;
+  let unsold_shares = shares
+    .checked_sub(ctx.accounts.pool.total_purchased.safe_add(ctx.accounts.pool.total_swap_fees_share)?)
+    .ok_or(error!(ErrorCode::InsufficientReserve))?;
 
   if unsold_shares != 0 {
 
-    token::transfer(
+    token_interface::transfer_checked(
       CpiContext::new_with_signer(
-          ctx.accounts.token_program.to_account_info(),
-          Transfer {  
+          ctx.accounts.share_token_program.to_account_info(),
+          TransferChecked {  
               from: ctx.accounts.share_vault.to_account_info(),
+              mint: ctx.accounts.share_mint.to_account_info(),
               to: ctx.accounts.pool_owner_share_vault.to_account_info(),
               authority: ctx.accounts.share_vault_authority.to_account_info(),
           },
           share_signer,
       ),
       unsold_shares,
+      ctx.accounts.share_mint.decimals,
     )?;
 
   }
@@ -179,7 +228,9 @@ pub fn handler(ctx: Context<Close>) -> Result<()> {
   ctx.accounts.pool.closed = true;
 
   emit!(ClosePool {
+    closer,
     platform_fees: platform_fees,
+    referrer_fees: ctx.accounts.pool.total_referred,
     swap_fees_asset: ctx.accounts.pool.total_swap_fees_asset,
     swap_fees_share: ctx.accounts.pool.total_swap_fees_share,
   });
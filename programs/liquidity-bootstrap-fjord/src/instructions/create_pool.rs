@@ -2,36 +2,80 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::utils::*;
 use crate::errors::ErrorCode;
-use anchor_spl::token::{self, TokenAccount, Transfer, Mint, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 #[derive(Accounts)]
 #[instruction(settings: PoolSettings, id: u64)]
 pub struct CreatePool<'info> {
 
-  #[account(zero)]
+  #[account(
+    init,
+    seeds = [POOL_SEED, lbp_factory_setting.key().as_ref(), id.to_le_bytes().as_ref()],
+    bump,
+    payer = depositor,
+    space = 8 + Pool::LEN,
+  )]
   pub pool: Box<Account<'info, Pool>>,
 
-  #[account(mut)]
-  pub asset_vault: Account<'info, TokenAccount>,
-  #[account(mut)]
-  pub share_vault: Account<'info, TokenAccount>,
+  #[account(address = settings.asset @ ErrorCode::InvalidAssetOrShare)]
+  pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+  #[account(address = settings.share @ ErrorCode::InvalidAssetOrShare)]
+  pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+  /// CHECK: This is not dangerous because we don't read or write from this account
+  #[account(
+    seeds = [b"asset".as_ref(), pool.key().as_ref()],
+    bump,
+  )]
+  pub asset_vault_authority: AccountInfo<'info>,
+
+  /// CHECK: This is not dangerous because we don't read or write from this account
+  #[account(
+    seeds = [b"share".as_ref(), pool.key().as_ref()],
+    bump,
+  )]
+  pub share_vault_authority: AccountInfo<'info>,
+
+  #[account(
+    init,
+    seeds = [b"asset_vault".as_ref(), pool.key().as_ref()],
+    bump,
+    payer = depositor,
+    token::mint = asset_mint,
+    token::authority = asset_vault_authority,
+    token::token_program = asset_token_program,
+  )]
+  pub asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+  #[account(
+    init,
+    seeds = [b"share_vault".as_ref(), pool.key().as_ref()],
+    bump,
+    payer = depositor,
+    token::mint = share_mint,
+    token::authority = share_vault_authority,
+    token::token_program = share_token_program,
+  )]
+  pub share_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
   #[account(
     mut,
     constraint = depositor_asset_vault.mint == asset_vault.mint,
     constraint = depositor_asset_vault.owner == depositor.key(),
   )]
-  pub depositor_asset_vault: Account<'info, TokenAccount>,
+  pub depositor_asset_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
   #[account(mut)]
-  pub depositor_share_vault: Account<'info, TokenAccount>,
+  pub depositor_share_vault: Box<InterfaceAccount<'info, TokenAccount>>,
   
   #[account(mut)]
   pub depositor: Signer<'info>,
 
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 
-  pub token_program: Program<'info, Token>,
+  #[account(address = *asset_mint.to_account_info().owner)]
+  pub asset_token_program: Interface<'info, TokenInterface>,
+  #[account(address = *share_mint.to_account_info().owner)]
+  pub share_token_program: Interface<'info, TokenInterface>,
   pub rent: Sysvar<'info, Rent>,
   pub system_program: Program<'info, System>,
 }
@@ -43,75 +87,64 @@ pub fn handler(
   id: u64, 
   shares: u64, 
   assets: u64,
-  share_vault_nonce: u8,
-  asset_vault_nonce: u8,
 ) -> Result<()> {
   let pool = &mut ctx.accounts.pool;
-  if pool.initialized {
-    return err!(ErrorCode::AlreadyInitialized);
-  }
-  
-  if settings.share == settings.asset || settings.share == Pubkey::default() || settings.asset == Pubkey::default() {
-    return err!(ErrorCode::InvalidAssetOrShare);
-  }
 
-  if settings.sale_end < settings.vest_end {
-    if settings.sale_end > settings.vest_cliff {
-      return err!(ErrorCode::InvalidVestCliff);
-    }
-    if settings.vest_cliff >= settings.vest_end {
-      return err!(ErrorCode::InvalidVestEnd);
-    }
-  }
-
-  if settings.weight_start < (0.01 * 1_000_000_000.0) as u64 || settings.weight_start > (0.99 * 1_000_000_000.0) as u64
-    || settings.weight_end < (0.01 * 1_000_000_000.0) as u64 || settings.weight_end > (0.99 * 1_000_000_000.0) as u64 {
-    return err!(ErrorCode::InvalidWeightConfig);
-  }
+  validate_pool_settings(&settings, assets)?;
 
-  if assets == 0 && settings.virtual_assets == 0 {
-    return err!(ErrorCode::InvalidAssetValue);
-  }
+  check_mint_extensions(&ctx.accounts.asset_mint.to_account_info())?;
+  check_mint_extensions(&ctx.accounts.share_mint.to_account_info())?;
 
   pool.id = id;
   pool.owner = *ctx.accounts.depositor.to_account_info().key;
+  pool.pending_owner = Pubkey::default();
+  pool.operator = Pubkey::default();
   pool.lbp_factory = *ctx.accounts.lbp_factory_setting.to_account_info().key;
   pool.settings = settings;
-  pool.initialized = true;
   pool.closed = false;
   pool.paused = false;
+  pool.paused_at = 0;
+  pool.total_paused_seconds = 0;
   pool.total_swap_fees_asset = 0;
   pool.total_swap_fees_share = 0;
   pool.total_purchased = 0;
-  pool.share_vault_nonce = share_vault_nonce;
-  pool.asset_vault_nonce = asset_vault_nonce;
-  pool.share_vault_authority = ctx.accounts.share_vault.owner;
-  pool.asset_vault_authority = ctx.accounts.asset_vault.owner;
+  pool.total_referred = 0;
+  pool.share_vault_nonce = ctx.bumps.share_vault_authority;
+  pool.asset_vault_nonce = ctx.bumps.asset_vault_authority;
+  pool.share_vault_authority = ctx.accounts.share_vault_authority.key();
+  pool.asset_vault_authority = ctx.accounts.asset_vault_authority.key();
   pool.share_vault = *ctx.accounts.share_vault.to_account_info().key;
   pool.asset_vault = *ctx.accounts.asset_vault.to_account_info().key;
+  pool.bump = ctx.bumps.pool;
 
-  token::transfer(
+  // Reserves are read from the vault balances, so transfer fees withheld on the
+  // deposits are accounted for without any extra bookkeeping
+  token_interface::transfer_checked(
     CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        ctx.accounts.asset_token_program.to_account_info(),
+        TransferChecked {
             from: ctx.accounts.depositor_asset_vault.to_account_info(),
+            mint: ctx.accounts.asset_mint.to_account_info(),
             to: ctx.accounts.asset_vault.to_account_info(),
             authority: ctx.accounts.depositor.to_account_info(),
         },
     ),
     assets,
+    ctx.accounts.asset_mint.decimals,
   )?;
 
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        ctx.accounts.share_token_program.to_account_info(),
+        TransferChecked {
             from: ctx.accounts.depositor_share_vault.to_account_info(),
+            mint: ctx.accounts.share_mint.to_account_info(),
             to: ctx.accounts.share_vault.to_account_info(),
             authority: ctx.accounts.depositor.to_account_info().clone(),
         },
     ),
     shares,
+    ctx.accounts.share_mint.decimals,
   )?;
 
   emit!(PoolCreated {
@@ -119,4 +152,142 @@ pub fn handler(
   });
 
   Ok(())
-}
\ No newline at end of file
+}
+
+/// Checks shared by `create_pool` and `update_pool_settings`, `assets` are the deposited assets.
+pub fn validate_pool_settings(settings: &PoolSettings, assets: u64) -> Result<()> {
+  if settings.share == settings.asset || settings.share == Pubkey::default() || settings.asset == Pubkey::default() {
+    return err!(ErrorCode::InvalidAssetOrShare);
+  }
+
+  // every curve is defined over the sale window, an empty one has no weights
+  if settings.sale_end <= settings.sale_start {
+    return err!(ErrorCode::SalePeriodLow);
+  }
+
+  if settings.sale_end < settings.vest_end {
+    if settings.sale_end > settings.vest_cliff {
+      return err!(ErrorCode::InvalidVestCliff);
+    }
+    if settings.vest_cliff >= settings.vest_end {
+      return err!(ErrorCode::InvalidVestEnd);
+    }
+  }
+
+  if settings.weight_start < (0.01 * 1_000_000_000.0) as u64 || settings.weight_start > (0.99 * 1_000_000_000.0) as u64
+    || settings.weight_end < (0.01 * 1_000_000_000.0) as u64 || settings.weight_end > (0.99 * 1_000_000_000.0) as u64 {
+    return err!(ErrorCode::InvalidWeightConfig);
+  }
+
+  if settings.max_share_price == 0 {
+    return err!(ErrorCode::InvalidMaxSharePrice);
+  }
+
+  // taking the whole output reserve would need an infinite input
+  if settings.max_percentage_in == 0 || settings.max_percentage_in > WEIGHT_PRECISION
+    || settings.max_percentage_out == 0 || settings.max_percentage_out >= WEIGHT_PRECISION {
+    return err!(ErrorCode::InvalidMaxPercentage);
+  }
+
+  validate_weight_curve(settings)?;
+  validate_purchase_limits(settings)?;
+  validate_vesting_schedule(settings)?;
+
+  if assets == 0 && settings.virtual_assets == 0 {
+    return err!(ErrorCode::InvalidAssetValue);
+  }
+
+  Ok(())
+}
+
+fn validate_purchase_limits(settings: &PoolSettings) -> Result<()> {
+  // a zero limit would block every buy, leave the limit unset instead
+  if settings.max_shares_per_user == Some(0) || settings.max_assets_per_user == Some(0) || settings.min_assets_per_buy == Some(0) {
+    return err!(ErrorCode::InvalidPurchaseLimits);
+  }
+
+  if let (Some(min_assets_per_buy), Some(max_assets_per_user)) = (settings.min_assets_per_buy, settings.max_assets_per_user) {
+    if min_assets_per_buy > max_assets_per_user {
+      return err!(ErrorCode::InvalidPurchaseLimits);
+    }
+  }
+
+  Ok(())
+}
+
+fn validate_vesting_schedule(settings: &PoolSettings) -> Result<()> {
+  let schedule = &settings.vesting_schedule;
+  if schedule.initial_unlock_bps > MAX_BPS {
+    return err!(ErrorCode::InvalidVestingSchedule);
+  }
+
+  match &schedule.release {
+    VestingRelease::Linear => {}
+    VestingRelease::Periodic { period } => {
+      if *period == 0 {
+        return err!(ErrorCode::InvalidVestingSchedule);
+      }
+    }
+    VestingRelease::Tranches { tranches } => {
+      if tranches.is_empty() || tranches.len() > MAX_VESTING_TRANCHES {
+        return err!(ErrorCode::InvalidVestingSchedule);
+      }
+      // offsets strictly increase, every tranche unlocks by vest end and the whole purchase is released
+      let vest_duration = settings.vest_end.saturating_sub(settings.vest_cliff);
+      let mut previous_offset: Option<u64> = None;
+      let mut total_bps = schedule.initial_unlock_bps as u64;
+      for tranche in tranches {
+        if tranche.bps == 0 || tranche.offset > vest_duration || previous_offset.is_some_and(|offset| tranche.offset <= offset) {
+          return err!(ErrorCode::InvalidVestingSchedule);
+        }
+        total_bps = total_bps.safe_add(tranche.bps as u64)?;
+        previous_offset = Some(tranche.offset);
+      }
+      if total_bps != MAX_BPS as u64 {
+        return err!(ErrorCode::InvalidVestingSchedule);
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn validate_weight_curve(settings: &PoolSettings) -> Result<()> {
+  if settings.weight_curve == WeightCurve::Linear {
+    return Ok(());
+  }
+
+  let total_seconds = settings.sale_end.safe_sub(settings.sale_start)?;
+
+  match &settings.weight_curve {
+    WeightCurve::Linear => {}
+    WeightCurve::ExponentialDecay { decay_rate } => {
+      if *decay_rate == 0 || *decay_rate > MAX_DECAY_RATE {
+        return err!(ErrorCode::InvalidWeightCurve);
+      }
+    }
+    WeightCurve::Step { steps } => {
+      if *steps < MIN_WEIGHT_STEPS || *steps > MAX_WEIGHT_STEPS {
+        return err!(ErrorCode::InvalidWeightCurve);
+      }
+    }
+    WeightCurve::PiecewiseLinear { breakpoints } => {
+      if breakpoints.is_empty() || breakpoints.len() > MAX_WEIGHT_BREAKPOINTS {
+        return err!(ErrorCode::InvalidWeightCurve);
+      }
+      // offsets strictly increase inside the sale window, start and end weights are implicit
+      let mut previous_offset = 0;
+      for breakpoint in breakpoints {
+        if breakpoint.offset <= previous_offset || breakpoint.offset >= total_seconds {
+          return err!(ErrorCode::InvalidWeightCurve);
+        }
+        if breakpoint.weight < (0.01 * 1_000_000_000.0) as u64 || breakpoint.weight > (0.99 * 1_000_000_000.0) as u64 {
+          return err!(ErrorCode::InvalidWeightCurve);
+        }
+        previous_offset = breakpoint.offset;
+      }
+    }
+  }
+
+  Ok(())
+}
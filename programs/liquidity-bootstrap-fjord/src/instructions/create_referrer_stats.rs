@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateReferrerStats<'info> {
+  #[account(mut)]
+  pub payer: Signer<'info>,
+
+  /// CHECK: only used as a seed, referral fees are claimed by this account
+  pub referrer: UncheckedAccount<'info>,
+
+  pub pool: Account<'info, Pool>,
+
+  #[account(
+    init,
+    seeds = [b"referrer_stats".as_ref(), pool.key().as_ref(), referrer.key().as_ref()],
+    bump,
+    payer = payer,
+    space = 8 + 8 + 8 + 1,
+  )]
+  pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+
+  pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+  ctx: Context<CreateReferrerStats>
+) -> Result<()> {
+
+  let referrer_stats = &mut ctx.accounts.referrer_stats;
+
+  referrer_stats.accrued = 0;
+  referrer_stats.claimed = 0;
+  referrer_stats.bump = ctx.bumps.referrer_stats;
+
+  Ok(())
+}
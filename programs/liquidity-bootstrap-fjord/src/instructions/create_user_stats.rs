@@ -13,7 +13,7 @@ pub struct CreateUserStats<'info> {
     seeds = [b"user_stats".as_ref(), pool.key().as_ref(), user.key().as_ref()],
     bump,
     payer = user,
-    space = 32 + 8 + 8 + 1,
+    space = 32 + 8 + 8 + 8 + 1,
   )]
   pub user_stats: Box<Account<'info, UserStats>>,
 
@@ -28,6 +28,7 @@ pub fn handler(
 
   user_stats.purchased = 0;
   user_stats.claimed = 0;
+  user_stats.assets_spent = 0;
   user_stats.bump = ctx.bumps.user_stats;
 
   Ok(())
@@ -14,7 +14,7 @@ pub struct Initialize<'info> {
     init,
     payer = authority,
     seeds = [b"lbp-factory".as_ref(), &id.to_le_bytes()],
-    space = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 1,
+    space = 8 + 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1,
     bump,
   )]
   pub lbp_factory_setting: Box<Account<'info, LBPFactorySetting>>,
@@ -28,6 +28,7 @@ pub fn handler(
   platform_fee: u64,
   referrer_fee: u64,
   swap_fee: u64,
+  close_grace_period: u64,
 ) -> Result<()> {
   let factory_settings = &mut ctx.accounts.lbp_factory_setting;
 
@@ -42,10 +43,13 @@ pub fn handler(
   factory_settings.id = id;
   factory_settings.bump = ctx.bumps.lbp_factory_setting;
   factory_settings.authority = *ctx.accounts.authority.key;
+  factory_settings.pending_authority = Pubkey::default();
   factory_settings.fee_recipient = fee_recipient;
   factory_settings.platform_fee = platform_fee;
   factory_settings.referrer_fee = referrer_fee;
   factory_settings.swap_fee = swap_fee;
+  factory_settings.close_grace_period = close_grace_period;
+  factory_settings.paused = false;
 
   emit!(FeeRecipientSet {
     fee_recipient,
@@ -63,6 +67,10 @@ pub fn handler(
     swap_fee,
   });
 
+  emit!(CloseGracePeriodSet {
+    close_grace_period,
+  });
+
   msg!("LBP Manager initialized");
 
   Ok(())
@@ -4,7 +4,12 @@ pub mod set_swap_fee;
 pub mod set_referrer_fee;
 pub mod set_fee_recipient;
 pub mod set_platform_fee;
+pub mod set_close_grace_period;
 pub mod transfer_ownership;
+pub mod accept_ownership;
+pub mod cancel_ownership_transfer;
+pub mod pause_factory;
+pub mod unpause_factory;
 pub mod create_pool;
 pub mod swap_exact_assets_for_shares;
 pub mod swap_assets_for_exact_shares;
@@ -14,19 +19,32 @@ pub mod preview_assets_in;
 pub mod preview_shares_out;
 pub mod preview_assets_out;
 pub mod preview_shares_in;
+pub mod preview_redeem;
 pub mod close;
 pub mod pause_pool;
 pub mod unpause_pool;
 pub mod redeem;
+pub mod redeem_for;
 pub mod create_user_stats;
 pub mod compute_reserves_and_weights;
+pub mod create_referrer_stats;
+pub mod claim_referrer_fees;
+pub mod update_pool_settings;
+pub mod transfer_pool_ownership;
+pub mod accept_pool_ownership;
+pub mod set_pool_operator;
 
 pub use initialize::*;
 pub use set_swap_fee::*;
 pub use set_referrer_fee::*;
 pub use set_fee_recipient::*;
 pub use set_platform_fee::*;
+pub use set_close_grace_period::*;
 pub use transfer_ownership::*;
+pub use accept_ownership::*;
+pub use cancel_ownership_transfer::*;
+pub use pause_factory::*;
+pub use unpause_factory::*;
 pub use create_pool::*;
 pub use swap_exact_assets_for_shares::*;
 pub use swap_assets_for_exact_shares::*;
@@ -34,6 +52,7 @@ pub use preview_assets_in::*;
 pub use preview_shares_out::*;
 pub use preview_assets_out::*;
 pub use preview_shares_in::*;
+pub use preview_redeem::*;
 pub use swap_shares_for_exact_assets::*;
 pub use swap_exact_shares_for_assets::*;
 pub use swap_exact_assets_for_shares::*;
@@ -42,5 +61,12 @@ pub use close::*;
 pub use pause_pool::*;
 pub use unpause_pool::*;
 pub use redeem::*;
+pub use redeem_for::*;
 pub use create_user_stats::*;
-pub use compute_reserves_and_weights::*;
\ No newline at end of file
+pub use compute_reserves_and_weights::*;
+pub use create_referrer_stats::*;
+pub use claim_referrer_fees::*;
+pub use update_pool_settings::*;
+pub use transfer_pool_ownership::*;
+pub use accept_pool_ownership::*;
+pub use set_pool_operator::*;
@@ -13,20 +13,33 @@ pub struct PausePool<'info> {
   pub authority: Signer<'info>,
 }
 
-pub fn handler(ctx: Context<PausePool>) -> Result<()> {
+pub fn handler(ctx: Context<PausePool>, reason: Option<u8>) -> Result<()> {
   let pool = &mut ctx.accounts.pool;
 
-  if pool.owner != *ctx.accounts.authority.key {
-    return err!(ErrorCode::Unauthorized);
-  }
+  pool.require_role(ctx.accounts.authority.key, PoolRole::Operator)?;
 
   if pool.closed {
     return err!(ErrorCode::PoolIsClosed);
   }
 
-  if !ctx.accounts.pool.paused {
-    ctx.accounts.pool.paused = true;
+  if pool.paused {
+    return Ok(());
   }
 
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
+
+  pool.paused = true;
+  pool.paused_at = unix_timestamp as u64;
+
+  emit!(PoolPaused {
+    pool: pool.key(),
+    signer: ctx.accounts.authority.key(),
+    timestamp: unix_timestamp,
+    reason,
+  });
+
   Ok(())
-}
\ No newline at end of file
+}
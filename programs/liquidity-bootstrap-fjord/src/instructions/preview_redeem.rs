@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct PreviewRedeem<'info> {
+  pub pool: Account<'info, Pool>,
+
+  /// CHECK: only used to derive the user stats address
+  pub user: UncheckedAccount<'info>,
+
+  #[account(
+    seeds = [b"user_stats".as_ref(), pool.key().as_ref(), user.key().as_ref()],
+    bump = user_stats.bump,
+  )]
+  pub user_stats: Account<'info, UserStats>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PreviewRedeemOutput {
+  pub claimable: u64, // redeemable right now, zero until the pool closes
+  pub vested: u64, // unlocked by the vesting schedule so far, claimed shares included
+  pub claimed: u64,
+  pub next_unlock_at: Option<u64>, // next time the vested amount grows, none once fully vested
+}
+
+pub fn handler(ctx: Context<PreviewRedeem>) -> Result<PreviewRedeemOutput> {
+  let pool = &ctx.accounts.pool;
+  let user_stats = &ctx.accounts.user_stats;
+  let curr_timestamp = Clock::get()?.unix_timestamp as u64;
+
+  // same math as redeem
+  let claimable = if pool.closed {
+    claimable_shares(&pool.settings, user_stats, curr_timestamp)?
+  } else {
+    0
+  };
+
+  Ok(PreviewRedeemOutput {
+    claimable,
+    vested: vested_shares(&pool.settings, user_stats.purchased, curr_timestamp)?,
+    claimed: user_stats.claimed,
+    next_unlock_at: next_unlock_at(&pool.settings, curr_timestamp),
+  })
+}
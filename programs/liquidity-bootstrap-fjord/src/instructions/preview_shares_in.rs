@@ -1,26 +1,29 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::ErrorCode;
-use anchor_spl::token::{Mint, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount};
 use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct PreviewSharesIn<'info> {
+  #[account(
+    constraint = pool.lbp_factory == lbp_factory_setting.key()
+  )]
   pub pool: Account<'info, Pool>,
 
-  pub pool_assets_account: Account<'info, TokenAccount>,
+  pub pool_assets_account: InterfaceAccount<'info, TokenAccount>,
 
-  pub pool_shares_account: Account<'info, TokenAccount>,
+  pub pool_shares_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
 
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 }
@@ -30,17 +33,21 @@ pub fn handler(ctx: Context<PreviewSharesIn>, assets_out: u64) -> Result<u64> {
   let pool = &mut ctx.accounts.pool;
   let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
 
+  if lbp_factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
+  }
+
+  if !pool.settings.selling_allowed {
+    return err!(ErrorCode::SellingDisallowed);
+  }
+
   let assets: u64 = ctx.accounts.pool_assets_account.amount;
   let shares: u64 = ctx.accounts.pool_shares_account.amount;
 
   let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
   let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let shares_in_result = preview_shares_in(pool, assets_out, assets, shares, assets_decimals, shares_decimals);
-  if shares_in_result.is_err() {
-    return err!(ErrorCode::MathError);
-  }
-
-  let mut shares_in: u64 = shares_in_result.unwrap();
+  let assets_sent = assets_out.safe_add(get_inverse_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_out)?)?;
+  let shares_in: u64 = preview_shares_in(pool, assets_sent, assets, shares, assets_decimals, shares_decimals)?;
 
   Ok(shares_in)
 }
\ No newline at end of file
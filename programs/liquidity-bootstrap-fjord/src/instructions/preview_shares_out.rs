@@ -1,26 +1,29 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::ErrorCode;
-use anchor_spl::token::{Mint, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount};
 use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct PreviewSharesOut<'info> {
+  #[account(
+    constraint = pool.lbp_factory == lbp_factory_setting.key()
+  )]
   pub pool: Account<'info, Pool>,
 
-  pub pool_assets_account: Account<'info, TokenAccount>,
+  pub pool_assets_account: InterfaceAccount<'info, TokenAccount>,
 
-  pub pool_shares_account: Account<'info, TokenAccount>,
+  pub pool_shares_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
 
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 }
@@ -30,17 +33,19 @@ pub fn handler(ctx: Context<PreviewSharesOut>, assets_in: u64) -> Result<u64> {
   let pool = &mut ctx.accounts.pool;
   let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
 
+  if lbp_factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
+  }
+
   let assets: u64 = ctx.accounts.pool_assets_account.amount;
   let shares: u64 = ctx.accounts.pool_shares_account.amount;
 
   let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
   let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let shares_out_result = preview_shares_out(pool, assets_in, assets, shares, assets_decimals, shares_decimals);
-  if shares_out_result.is_err() {
-    return err!(ErrorCode::MathError);
-  }
-
-  let mut shares_out: u64 = shares_out_result.unwrap();
+  let assets_received = assets_in.safe_sub(get_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_in)?)?;
+  // Buys only price the deposit net of the swap fee
+  let swap_fee = apply_rate(assets_received, lbp_factory_setting.swap_fee)?;
+  let shares_out: u64 = preview_shares_out(pool, assets_received.safe_sub(swap_fee)?, assets, shares, assets_decimals, shares_decimals)?;
 
   Ok(shares_out)
 }
\ No newline at end of file
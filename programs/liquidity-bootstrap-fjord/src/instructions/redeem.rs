@@ -2,7 +2,8 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::utils::*;
 use crate::errors::ErrorCode;
-use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 #[derive(Accounts)]
 pub struct Redeem<'info> {
@@ -21,7 +22,7 @@ pub struct Redeem<'info> {
         constraint = share_vault.mint == pool.settings.share,
         constraint = share_vault.owner == pool.share_vault_authority,
     )]
-    pub share_vault: Account<'info, TokenAccount>,
+    pub share_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: This is not dangerous because we don't read or write from this account
     #[account(
@@ -42,10 +43,19 @@ pub struct Redeem<'info> {
     )]
     pub buyer_stats: Box<Account<'info, UserStats>>,
 
-    #[account(mut)]
-    pub recipient_share_vault: Account<'info, TokenAccount>,
+    /// The user's share ATA unless a recipient is passed explicitly
+    #[account(
+        mut,
+        constraint = recipient_share_vault.mint == pool.settings.share @ ErrorCode::InvalidRecipientShareVault,
+    )]
+    pub recipient_share_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = share_mint.key() == pool.settings.share,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
 
@@ -53,68 +63,103 @@ pub struct Redeem<'info> {
 
 pub fn handler(
     ctx: Context<Redeem>,
+    amount: Option<u64>,
+    recipient: Option<Pubkey>,
 ) -> Result<()> {
+    let recipient_key = ctx.accounts.recipient_share_vault.key();
+    match recipient {
+        // shares may go elsewhere only when the user names the destination
+        Some(recipient) => {
+            if recipient != recipient_key {
+                return err!(ErrorCode::InvalidRecipientShareVault);
+            }
+        }
+        None => {
+            let user_ata = get_associated_token_address_with_program_id(
+                ctx.accounts.user.key,
+                &ctx.accounts.share_mint.key(),
+                &ctx.accounts.token_program.key(),
+            );
+            if recipient_key != user_ata || ctx.accounts.recipient_share_vault.owner != ctx.accounts.user.key() {
+                return err!(ErrorCode::InvalidRecipientShareVault);
+            }
+        }
+    }
+
     let buyer_stats = &mut ctx.accounts.buyer_stats;
 
     if !ctx.accounts.pool.closed {
         return err!(ErrorCode::RedeemingDisallowed);
     }
 
-    let vest_end = ctx.accounts.pool.settings.vest_end;
-    let vest_cliff = ctx.accounts.pool.settings.vest_cliff;
-    let sale_end = ctx.accounts.pool.settings.sale_end;
-    let vest_shares = sale_end < vest_end;
-
     let curr_timestamp = Clock::get()?.unix_timestamp as u64;
-    
-    let mut claimable: u64;
-    let claimed = buyer_stats.claimed;
-    
-    if vest_shares && vest_end > curr_timestamp {
-        let shares = buyer_stats.purchased;
-        // if not reached vest cliff, no shares can be claimed
-        if curr_timestamp < vest_cliff {
-            claimable = 0;
-        // other wise distribute shares linearly
-        } else {
-            claimable = shares * (curr_timestamp - vest_cliff) / (vest_end - vest_cliff);
-            claimable -= claimed;
-        }
-    } else {
-        claimable = buyer_stats.purchased - claimed;
-    }
+
+    // shares unlock following the pool's vesting schedule, all of them once vesting ends
+    let claimable = claimable_shares(&ctx.accounts.pool.settings, buyer_stats, curr_timestamp)?;
+
+    // users may leave part of the claimable shares in the vault for later
+    let claimable = match amount {
+        Some(amount) if amount > claimable => return err!(ErrorCode::RedeemAmountExceedsClaimable),
+        Some(amount) => amount,
+        None => claimable,
+    };
 
     if claimable == 0 {
         return err!(ErrorCode::NoSharesToClaim);
     }
 
-    let seeds = &[
-      b"share".as_ref(),
-      ctx.accounts.pool.to_account_info().key.as_ref(),
-      &[ctx.accounts.pool.share_vault_nonce],
-    ];
-    let signer = &[&seeds[..]];
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.share_vault.to_account_info(),
-                to: ctx.accounts.recipient_share_vault.to_account_info(),
-                authority: ctx.accounts.share_vault_authority.to_account_info()
-            },
-            signer,
-        ),
+    transfer_vested_shares(
+        &ctx.accounts.pool,
+        &ctx.accounts.share_vault,
+        &ctx.accounts.share_vault_authority,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.token_program,
+        ctx.accounts.recipient_share_vault.to_account_info(),
         claimable,
     )?;
-    
-    buyer_stats.claimed += claimable;
+
+    buyer_stats.claimed = buyer_stats.claimed.safe_add(claimable)?;
 
     emit!(Redeemed {
-        recipient: ctx.accounts.recipient_share_vault.to_account_info().key().clone(),
+        user: ctx.accounts.user.key(),
+        recipient: recipient_key,
+        custom_recipient: recipient.is_some(),
         shares: claimable,
         total_claimed: buyer_stats.claimed
     });
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Sends vested shares out of the pool share vault, signed by the vault authority.
+pub(crate) fn transfer_vested_shares<'info>(
+    pool: &Account<'info, Pool>,
+    share_vault: &InterfaceAccount<'info, TokenAccount>,
+    share_vault_authority: &AccountInfo<'info>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+      b"share".as_ref(),
+      pool.to_account_info().key.as_ref(),
+      &[pool.share_vault_nonce],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: share_vault.to_account_info(),
+                mint: share_mint.to_account_info(),
+                to,
+                authority: share_vault_authority.clone(),
+            },
+            signer,
+        ),
+        amount,
+        share_mint.decimals,
+    )
+}
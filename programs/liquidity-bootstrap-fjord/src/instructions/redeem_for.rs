@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+use crate::instructions::redeem::transfer_vested_shares;
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Accounts passed per user in `remaining_accounts`: the user, their stats and their share ATA.
+pub const REDEEM_FOR_ACCOUNTS: usize = 3;
+
+#[derive(Accounts)]
+pub struct RedeemFor<'info> {
+
+    /// Anyone can crank redemptions, they pay for the ATAs that need to be created
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = pool.lbp_factory == lbp_factory_setting.key()
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        mut,
+        constraint = share_vault.mint == pool.settings.share,
+        constraint = share_vault.owner == pool.share_vault_authority,
+    )]
+    pub share_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(
+        seeds = [
+            b"share".as_ref(),
+            pool.to_account_info().key.as_ref()
+        ],
+        bump = pool.share_vault_nonce,
+    )]
+    pub share_vault_authority: AccountInfo<'info>,
+
+    pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
+
+    #[account(
+        constraint = share_mint.key() == pool.settings.share,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RedeemFor<'info>>,
+) -> Result<()> {
+    if !ctx.accounts.pool.closed {
+        return err!(ErrorCode::RedeemingDisallowed);
+    }
+
+    let batch = ctx.remaining_accounts.chunks_exact(REDEEM_FOR_ACCOUNTS);
+    if ctx.remaining_accounts.is_empty() || !batch.remainder().is_empty() {
+        return err!(ErrorCode::InvalidRedeemAccounts);
+    }
+
+    let curr_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_key = ctx.accounts.pool.key();
+
+    for accounts in batch {
+        let [user, user_stats_info, recipient_share_vault] = accounts else {
+            return err!(ErrorCode::InvalidRedeemAccounts);
+        };
+
+        let mut buyer_stats = Account::<UserStats>::try_from(user_stats_info)?;
+        let expected_stats = Pubkey::create_program_address(
+            &[b"user_stats".as_ref(), pool_key.as_ref(), user.key.as_ref(), &[buyer_stats.bump]],
+            ctx.program_id,
+        ).map_err(|_| error!(ErrorCode::InvalidRedeemAccounts))?;
+        if expected_stats != user_stats_info.key() {
+            return err!(ErrorCode::InvalidRedeemAccounts);
+        }
+
+        // shares always go to the user's canonical ATA
+        let expected_vault = get_associated_token_address_with_program_id(
+            user.key,
+            &ctx.accounts.share_mint.key(),
+            &ctx.accounts.token_program.key(),
+        );
+        if expected_vault != recipient_share_vault.key() {
+            return err!(ErrorCode::InvalidRedeemAccounts);
+        }
+
+        // users with nothing to claim are skipped so one of them can't fail the whole batch
+        let claimable = claimable_shares(&ctx.accounts.pool.settings, &buyer_stats, curr_timestamp)?;
+        if claimable == 0 {
+            continue;
+        }
+
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: ctx.accounts.payer.to_account_info(),
+                associated_token: recipient_share_vault.clone(),
+                authority: user.clone(),
+                mint: ctx.accounts.share_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+
+        transfer_vested_shares(
+            &ctx.accounts.pool,
+            &ctx.accounts.share_vault,
+            &ctx.accounts.share_vault_authority,
+            &ctx.accounts.share_mint,
+            &ctx.accounts.token_program,
+            recipient_share_vault.clone(),
+            claimable,
+        )?;
+
+        buyer_stats.claimed = buyer_stats.claimed.safe_add(claimable)?;
+        buyer_stats.exit(ctx.program_id)?;
+
+        emit!(Redeemed {
+            user: user.key(),
+            recipient: recipient_share_vault.key(),
+            custom_recipient: false,
+            shares: claimable,
+            total_claimed: buyer_stats.claimed
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct SetCloseGracePeriod<'info> {
+  #[account(mut, has_one = authority)]
+  pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
+  pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetCloseGracePeriod>, close_grace_period: u64) -> Result<()> {
+  let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
+
+  lbp_factory_setting.close_grace_period = close_grace_period;
+
+  emit!(CloseGracePeriodSet {
+    close_grace_period,
+  });
+
+  Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct SetPoolOperator<'info> {
+
+  #[account(mut)]
+  pub pool: Box<Account<'info, Pool>>,
+
+  pub authority: Signer<'info>,
+}
+
+/// Sets the pool operator, the default pubkey removes it.
+pub fn handler(ctx: Context<SetPoolOperator>, operator: Pubkey) -> Result<()> {
+  let pool = &mut ctx.accounts.pool;
+
+  pool.require_role(ctx.accounts.authority.key, PoolRole::Owner)?;
+
+  pool.operator = operator;
+
+  emit!(PoolOperatorSet {
+    pool: pool.key(),
+    operator,
+  });
+
+  Ok(())
+}
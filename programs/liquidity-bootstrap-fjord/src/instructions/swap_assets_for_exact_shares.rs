@@ -1,11 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
-use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use crate::errors::ErrorCode;
 use crate::state::*;
 use crate::utils::*;
 #[derive(Accounts)]
-#[instruction(recipient: Pubkey)]
+#[instruction(recipient: Pubkey, shares_out: u64, max_assets_in: u64, referrer: Option<Pubkey>, allowlist: Option<AllowlistProof>)]
 pub struct SwapAssetsForExactShares<'info> {
   #[account(mut)]
   pub depositor: Signer<'info>,
@@ -21,31 +20,31 @@ pub struct SwapAssetsForExactShares<'info> {
     constraint = pool_assets_account.mint == pool.settings.asset,
     constraint = pool_assets_account.owner == pool.asset_vault_authority,
   )]
-  pub pool_assets_account: Account<'info, TokenAccount>,
+  pub pool_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
     constraint = pool_shares_account.mint == pool.settings.share,
     constraint = pool_shares_account.owner == pool.share_vault_authority,
   )]
-  pub pool_shares_account: Account<'info, TokenAccount>,
+  pub pool_shares_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
   
   #[account(
     mut,
     constraint = depositor_assets_account.mint == pool.settings.asset,
     constraint = depositor_assets_account.owner == depositor.key(),
   )]
-  pub depositor_assets_account: Account<'info, TokenAccount>,
+  pub depositor_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
@@ -54,8 +53,15 @@ pub struct SwapAssetsForExactShares<'info> {
   )]
   pub recipient_user_stats: Box<Account<'info, UserStats>>,
 
+  #[account(
+    mut,
+    seeds = [b"referrer_stats".as_ref(), pool.key().as_ref(), referrer.unwrap_or_default().as_ref()],
+    bump = referrer_stats.bump,
+  )]
+  pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
-  pub token_program: Program<'info, Token>,
+  pub token_program: Interface<'info, TokenInterface>,
   pub rent: Sysvar<'info, Rent>,
   pub system_program: Program<'info, System>,
 }
@@ -64,6 +70,8 @@ pub fn handler (
   recipient: Pubkey,
   shares_out: u64,
   max_assets_in: u64,
+  referrer: Option<Pubkey>,
+  allowlist: Option<AllowlistProof>,
 ) -> Result<u64> {
   // Get the pool and manager
   let pool = &mut ctx.accounts.pool;
@@ -79,47 +87,92 @@ pub fn handler (
   if pool.paused {
     return err!(ErrorCode::PoolIsPaused);
   }
+
+  if factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
+  }
+
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
+
+  if (unix_timestamp as i128) < (pool.settings.sale_start as i128) {
+    return err!(ErrorCode::TradingNotStarted);
+  }
+
+  if (unix_timestamp as i128) >= (pool.settings.sale_end as i128) {
+    return err!(ErrorCode::TradingEnded);
+  }
+
+  check_allowlist(&pool.settings, &recipient, recipient_user_stats.purchased.safe_add(shares_out)?, &allowlist)?;
   
   // Preview the assets in
   let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
   let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let assets_in_result = preview_assets_in(pool, shares_out, assets, shares, assets_decimals, shares_decimals);
-  if assets_in_result.is_err() {
-    return err!(ErrorCode::MathError);
-  }
-  let mut assets_in = assets_in_result.unwrap();
+  let mut assets_in = preview_assets_in(pool, shares_out, assets, shares, assets_decimals, shares_decimals)?;
+
+  let swap_fees: u64 = apply_rate(assets_in, factory_setting.swap_fee)?;
+  assets_in = assets_in.safe_add(swap_fees)?;
+  pool.total_swap_fees_asset = pool.total_swap_fees_asset.safe_add(swap_fees)?;
 
-  let swap_fees: u64 = (assets_in * factory_setting.swap_fee) / 1_000_000_000;
-  assets_in += swap_fees;
-  pool.total_swap_fees_asset += swap_fees;
+  // Transfer fee mints withhold part of the deposit, gross it up so the pool
+  // receives the full amount
+  let assets_paid = assets_in.safe_add(get_inverse_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_in)?)?;
 
   // Add slippage error function
-  if assets_in > max_assets_in {
+  if assets_paid > max_assets_in {
     return err!(ErrorCode::SlippageExceeded);
   }
 
+  check_purchase_limits(&pool.settings, recipient_user_stats, assets_in, shares_out)?;
+
   // Call the swapAssetsForExactShares function 
-  if assets + assets_in - swap_fees >= pool.settings.max_assets_in {
+  if assets.safe_add(assets_in)?.safe_sub(swap_fees)? >= pool.settings.max_assets_in {
     return err!(ErrorCode::MaxAssetsInExceeded);
   }
 
-  token::transfer(
+  let referrer_fees = match referrer {
+    Some(referrer) => {
+      if referrer == recipient || referrer == ctx.accounts.depositor.key() {
+        return err!(ErrorCode::InvalidReferrer);
+      }
+      let referrer_stats = match ctx.accounts.referrer_stats.as_mut() {
+        Some(referrer_stats) => referrer_stats,
+        None => return err!(ErrorCode::InvalidReferrer),
+      };
+      let referrer_fees = apply_rate(assets_in.safe_sub(swap_fees)?, factory_setting.referrer_fee)?;
+      referrer_stats.accrued = referrer_stats.accrued.safe_add(referrer_fees)?;
+      emit!(ReferrerFeeAccrued {
+        referrer,
+        assets: referrer_fees,
+      });
+      referrer_fees
+    }
+    None => 0,
+  };
+  pool.total_referred = pool.total_referred.safe_add(referrer_fees)?;
+
+  token_interface::transfer_checked(
     CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        TransferChecked {
             from: ctx.accounts.depositor_assets_account.to_account_info(),
+            mint: ctx.accounts.pool_assets_mint.to_account_info(),
             to: ctx.accounts.pool_assets_account.to_account_info(),
             authority: ctx.accounts.depositor.to_account_info(),
         },
     ),
-    assets_in,
+    assets_paid,
+    ctx.accounts.pool_assets_mint.decimals,
   )?;
-  let total_purchased_after = pool.total_purchased + shares_out;
-  if total_purchased_after >= pool.settings.max_shares_out || total_purchased_after > shares {
+  let total_purchased_after = pool.total_purchased.safe_add(shares_out)?;
+  if total_purchased_after >= pool.settings.max_shares_out || total_purchased_after > shares.safe_sub(pool.total_swap_fees_share)? {
     return err!(ErrorCode::MaxSharesExceeded);
   }
   pool.total_purchased = total_purchased_after;
-  recipient_user_stats.purchased += shares_out;
+  recipient_user_stats.purchased = recipient_user_stats.purchased.safe_add(shares_out)?;
+  recipient_user_stats.assets_spent = recipient_user_stats.assets_spent.safe_add(assets_in)?;
 
   emit!(Buy {
     caller: *ctx.accounts.depositor.key,
@@ -128,5 +181,5 @@ pub fn handler (
     shares: shares_out,
     swap_fee: swap_fees,
   });
-  Ok(assets_in)
+  Ok(assets_paid)
 }
\ No newline at end of file
@@ -1,12 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
-use anchor_spl::token::{self, TokenAccount, Token, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use crate::state::*;
 use crate::utils::*;
 use crate::errors::ErrorCode;
 
 #[derive(Accounts)]
-#[instruction(recipient: Pubkey)]
+#[instruction(recipient: Pubkey, assets_in: u64, min_shares_out: u64, referrer: Option<Pubkey>, allowlist: Option<AllowlistProof>)]
 pub struct SwapExactAssetsForShares<'info> {
 
   #[account(mut)]
@@ -25,24 +24,24 @@ pub struct SwapExactAssetsForShares<'info> {
     constraint = pool_share_vault.mint == pool.settings.share,
     constraint = pool_share_vault.owner == pool.share_vault_authority,
   )]
-  pub pool_share_vault: Account<'info, TokenAccount>,
+  pub pool_share_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     mut,
     constraint = pool_asset_vault.mint == pool.settings.asset,
     constraint = pool_asset_vault.owner == pool.asset_vault_authority,
   )]
-  pub pool_asset_vault: Account<'info, TokenAccount>,
+  pub pool_asset_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
 
   /// CHECK: This is not dangerous because we don't read or write from this account
   #[account(
@@ -50,7 +49,7 @@ pub struct SwapExactAssetsForShares<'info> {
     constraint = depositor_asset_vault.mint == pool.settings.asset,
     constraint = depositor_asset_vault.owner == depositor.key(),
   )]
-  pub depositor_asset_vault: Account<'info, TokenAccount>,
+  pub depositor_asset_vault: InterfaceAccount<'info, TokenAccount>,
 
   #[account(   
     mut,
@@ -59,7 +58,14 @@ pub struct SwapExactAssetsForShares<'info> {
   )]
   pub recipient_user_stats: Box<Account<'info, UserStats>>,
 
-  pub token_program: Program<'info, Token>,
+  #[account(
+    mut,
+    seeds = [b"referrer_stats".as_ref(), pool.key().as_ref(), referrer.unwrap_or_default().as_ref()],
+    bump = referrer_stats.bump,
+  )]
+  pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+
+  pub token_program: Interface<'info, TokenInterface>,
   pub rent: Sysvar<'info, Rent>,
   pub system_program: Program<'info, System>,
 }
@@ -70,6 +76,8 @@ pub fn handler(
   recipient: Pubkey,
   assets_in: u64,
   min_shares_out: u64,
+  referrer: Option<Pubkey>,
+  allowlist: Option<AllowlistProof>,
 ) -> Result<u64> {
   let pool = &mut ctx.accounts.pool;
   let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
@@ -85,52 +93,96 @@ pub fn handler(
   if pool.paused {
     return err!(ErrorCode::PoolIsPaused);
   }
-  
-  let swap_fee: u64 = (assets_in * lbp_factory_setting.swap_fee) / 1_000_000_000;
-  pool.total_swap_fees_asset += swap_fee;
 
-  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
-  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let shares_out_result = preview_shares_out(pool, assets_in, assets, shares, assets_decimals, shares_decimals);
-  if shares_out_result.is_err() {
-    return err!(ErrorCode::MathError);
+  if lbp_factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
+  }
+
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
+
+  if (unix_timestamp as i128) < (pool.settings.sale_start as i128) {
+    return err!(ErrorCode::TradingNotStarted);
+  }
+
+  if (unix_timestamp as i128) >= (pool.settings.sale_end as i128) {
+    return err!(ErrorCode::TradingEnded);
   }
 
-  let shares_out = shares_out_result.unwrap();
+  // Transfer fee mints withhold part of the deposit, only the amount the pool
+  // actually receives is used for pricing and fees
+  let assets_received = assets_in.safe_sub(get_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_in)?)?;
+
+  // The swap fee stays in the vault for the fee recipient, only the rest of the deposit buys shares
+  let swap_fee: u64 = apply_rate(assets_received, lbp_factory_setting.swap_fee)?;
+
+  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
+  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
+  let shares_out = preview_shares_out(pool, assets_received.safe_sub(swap_fee)?, assets, shares, assets_decimals, shares_decimals)?;
+  pool.total_swap_fees_asset = pool.total_swap_fees_asset.safe_add(swap_fee)?;
 
   if shares_out < min_shares_out {
     return err!(ErrorCode::SlippageExceeded);
   }
 
-  if assets + assets_in - swap_fee >= pool.settings.max_assets_in {
+  check_allowlist(&pool.settings, &recipient, recipient_user_stats.purchased.safe_add(shares_out)?, &allowlist)?;
+  check_purchase_limits(&pool.settings, recipient_user_stats, assets_received, shares_out)?;
+
+  if assets.safe_add(assets_received)?.safe_sub(swap_fee)? >= pool.settings.max_assets_in {
     return err!(ErrorCode::MaxAssetsInExceeded);
   }
 
-  token::transfer(
+  let referrer_fees = match referrer {
+    Some(referrer) => {
+      if referrer == recipient || referrer == ctx.accounts.depositor.key() {
+        return err!(ErrorCode::InvalidReferrer);
+      }
+      let referrer_stats = match ctx.accounts.referrer_stats.as_mut() {
+        Some(referrer_stats) => referrer_stats,
+        None => return err!(ErrorCode::InvalidReferrer),
+      };
+      let referrer_fees = apply_rate(assets_received.safe_sub(swap_fee)?, lbp_factory_setting.referrer_fee)?;
+      referrer_stats.accrued = referrer_stats.accrued.safe_add(referrer_fees)?;
+      emit!(ReferrerFeeAccrued {
+        referrer,
+        assets: referrer_fees,
+      });
+      referrer_fees
+    }
+    None => 0,
+  };
+  pool.total_referred = pool.total_referred.safe_add(referrer_fees)?;
+
+  token_interface::transfer_checked(
     CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        TransferChecked {
             from: ctx.accounts.depositor_asset_vault.to_account_info(),
+            mint: ctx.accounts.pool_assets_mint.to_account_info(),
             to: ctx.accounts.pool_asset_vault.to_account_info(),
             authority: ctx.accounts.depositor.to_account_info(),
         },
     ),
     assets_in,
+    ctx.accounts.pool_assets_mint.decimals,
   )?;
 
-  let total_purchased_after = pool.total_purchased + shares_out;
+  let total_purchased_after = pool.total_purchased.safe_add(shares_out)?;
 
-  if total_purchased_after >= pool.settings.max_shares_out || total_purchased_after > shares {
+  if total_purchased_after >= pool.settings.max_shares_out || total_purchased_after > shares.safe_sub(pool.total_swap_fees_share)? {
     return err!(ErrorCode::MaxSharesExceeded);
   }
 
   pool.total_purchased = total_purchased_after;
-  recipient_user_stats.purchased += shares_out;
+  recipient_user_stats.purchased = recipient_user_stats.purchased.safe_add(shares_out)?;
+  recipient_user_stats.assets_spent = recipient_user_stats.assets_spent.safe_add(assets_received)?;
 
   emit!(Buy {
     caller: *ctx.accounts.depositor.key,
     recipient: recipient,
-    assets: assets_in,
+    assets: assets_received,
     shares: shares_out,
     swap_fee: swap_fee,
   });
@@ -1,5 +1,4 @@
-use anchor_spl::token::Mint;
-use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use anchor_lang::prelude::*;
 use crate::errors::ErrorCode;
 use crate::utils::*;
@@ -22,7 +21,7 @@ pub struct SwapExactSharesForAssets<'info> {
     constraint = pool_assets_account.mint == pool.settings.asset,
     constraint = pool_assets_account.owner == pool.asset_vault_authority,
   )]
-  pub pool_assets_account: Account<'info, TokenAccount>,
+  pub pool_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   /// CHECK: This is not dangerous because we don't read or write from this account
   #[account(
@@ -39,35 +38,35 @@ pub struct SwapExactSharesForAssets<'info> {
     constraint = pool_shares_account.mint == pool.settings.share,
     constraint = pool_shares_account.owner == pool.share_vault_authority,
   )]
-  pub pool_shares_account: Account<'info, TokenAccount>,
+  pub pool_shares_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     mut,
-    constraint = depositor_assets_account.mint == pool.settings.asset,
-    constraint = depositor_assets_account.owner == depositor.key(),
+    constraint = recipient_assets_account.mint == pool.settings.asset,
+    constraint = recipient_assets_account.owner == recipient,
   )]
-  pub depositor_assets_account: Account<'info, TokenAccount>,
+  pub recipient_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(   
     mut,
-    seeds = [b"user_stats".as_ref(), pool.key().as_ref(), recipient.key().as_ref()],
+    seeds = [b"user_stats".as_ref(), pool.key().as_ref(), depositor.key().as_ref()],
     bump = buyer_stats.bump,
   )]
   pub buyer_stats: Box<Account<'info, UserStats>>,
 
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 
-  pub token_program: Program<'info, Token>,
+  pub token_program: Interface<'info, TokenInterface>,
   pub rent: Sysvar<'info, Rent>,
   pub system_program: Program<'info, System>,
 }
@@ -94,35 +93,45 @@ pub fn handler(
     return err!(ErrorCode::PoolIsPaused);
   }
 
-  let swap_fee = (shares_in * factory_setting.swap_fee) / 1_000_000_000;
-  pool.total_swap_fees_share += swap_fee;
+  if factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
+  }
 
-  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
-  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let assets_out_result = preview_assets_out(pool, shares_in - swap_fee, assets, shares, assets_decimals, shares_decimals);
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
 
-  if assets_out_result.is_err() {
-    return err!(ErrorCode::MathError);
+  if (unix_timestamp as i128) < (pool.settings.sale_start as i128) {
+    return err!(ErrorCode::TradingNotStarted);
   }
 
-  let assets_out = assets_out_result.unwrap();
-
-  if assets_out < min_assets_out {
-    return err!(ErrorCode::SlippageExceeded);
+  if (unix_timestamp as i128) >= (pool.settings.sale_end as i128) {
+    return err!(ErrorCode::TradingEnded);
   }
 
-  if assets >= pool.settings.max_assets_in {
-    return err!(ErrorCode::MaxAssetsInExceeded);
+  if !pool.settings.selling_allowed {
+    return err!(ErrorCode::SellingDisallowed);
   }
 
-  let total_purchased_before = pool.total_purchased;
+  let swap_fee = apply_rate(shares_in, factory_setting.swap_fee)?;
 
-  if total_purchased_before >= pool.settings.max_shares_out || total_purchased_before > shares {
-    return err!(ErrorCode::MaxSharesExceeded);
+  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
+  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
+  let assets_out = preview_assets_out(pool, shares_in.safe_sub(swap_fee)?, assets, shares, assets_decimals, shares_decimals)?;
+  check_sell_payout(pool, assets, assets_out)?;
+  pool.total_swap_fees_share = pool.total_swap_fees_share.safe_add(swap_fee)?;
+
+  // Transfer fee mints withhold part of the payout, slippage applies to what the seller receives
+  let assets_received = assets_out.safe_sub(get_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_out)?)?;
+
+  if assets_received < min_assets_out {
+    return err!(ErrorCode::SlippageExceeded);
   }
 
-  buyer_stats.purchased -= shares_in;
-  pool.total_purchased = total_purchased_before - shares_in;
+  // Sellers can only sell the shares they bought and haven't redeemed, the
+  // swap fee part stays in the vault for the fee recipient
+  remove_sold_shares(pool, buyer_stats, shares_in)?;
 
   let seeds = &[
     b"asset".as_ref(),
@@ -131,25 +140,28 @@ pub fn handler(
   ];
   let signer = &[&seeds[..]];
 
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        TransferChecked {
             from: ctx.accounts.pool_assets_account.to_account_info(),
-            to: ctx.accounts.depositor_assets_account.to_account_info(),
+            mint: ctx.accounts.pool_assets_mint.to_account_info(),
+            to: ctx.accounts.recipient_assets_account.to_account_info(),
             authority: ctx.accounts.asset_vault_authority.to_account_info(),
         },
         signer,
     ),
     assets_out,
+    ctx.accounts.pool_assets_mint.decimals,
   )?;
 
   emit!(Sell {
     caller: *ctx.accounts.depositor.to_account_info().key,
+    recipient,
     shares: shares_in,
     assets: assets_out,
     swap_fee: swap_fee
   });
 
-  Ok(assets_out)
+  Ok(assets_received)
 }
\ No newline at end of file
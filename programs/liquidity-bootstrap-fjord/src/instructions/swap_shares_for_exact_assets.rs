@@ -1,5 +1,4 @@
-use anchor_spl::token::Mint;
-use anchor_spl::token::{self, TokenAccount, Transfer, Token};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use anchor_lang::prelude::*;
 use crate::errors::ErrorCode;
 use crate::utils::*;
@@ -22,7 +21,7 @@ pub struct SwapSharesForExactAssets<'info> {
     constraint = pool_assets_account.mint == pool.settings.asset,
     constraint = pool_assets_account.owner == pool.asset_vault_authority,
   )]
-  pub pool_assets_account: Account<'info, TokenAccount>,
+  pub pool_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   /// CHECK: This is not dangerous because we don't read or write from this account
   #[account(
@@ -39,24 +38,24 @@ pub struct SwapSharesForExactAssets<'info> {
     constraint = pool_shares_account.mint == pool.settings.share,
     constraint = pool_shares_account.owner == pool.share_vault_authority,
   )]
-  pub pool_shares_account: Account<'info, TokenAccount>,
+  pub pool_shares_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(
     constraint = pool_assets_mint.key() == pool.settings.asset,
   )]
-  pub pool_assets_mint: Account<'info, Mint>,
+  pub pool_assets_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     constraint = pool_shares_mint.key() == pool.settings.share,
   )]
-  pub pool_shares_mint: Account<'info, Mint>,
+  pub pool_shares_mint: InterfaceAccount<'info, Mint>,
 
   #[account(
     mut,
-    constraint = depositor_assets_account.mint == pool.settings.asset,
-    constraint = depositor_assets_account.owner == depositor.key(),
+    constraint = recipient_assets_account.mint == pool.settings.asset,
+    constraint = recipient_assets_account.owner == recipient,
   )]
-  pub depositor_assets_account: Account<'info, TokenAccount>,
+  pub recipient_assets_account: InterfaceAccount<'info, TokenAccount>,
 
   #[account(   
     mut,
@@ -68,7 +67,7 @@ pub struct SwapSharesForExactAssets<'info> {
   #[account(mut)]
   pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
 
-  pub token_program: Program<'info, Token>,
+  pub token_program: Interface<'info, TokenInterface>,
   pub rent: Sysvar<'info, Rent>,
   pub system_program: Program<'info, System>,
 }
@@ -95,36 +94,48 @@ pub fn handler(
     return err!(ErrorCode::PoolIsPaused);
   }
 
-  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
-  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
-  let shares_in_result = preview_shares_in(pool, assets_out, assets, shares, assets_decimals, shares_decimals);
-
-  if shares_in_result.is_err() {
-    return err!(ErrorCode::MathError);
+  if factory_setting.paused {
+    return err!(ErrorCode::FactoryIsPaused);
   }
 
-  let mut shares_in: u64 = shares_in_result.unwrap();
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
 
-  let swap_fee = (shares_in * factory_setting.swap_fee) / 1_000_000_000;
-  shares_in += swap_fee;
+  if (unix_timestamp as i128) < (pool.settings.sale_start as i128) {
+    return err!(ErrorCode::TradingNotStarted);
+  }
 
-  if shares_in > max_shares_in {
-    return err!(ErrorCode::SlippageExceeded);
+  if (unix_timestamp as i128) >= (pool.settings.sale_end as i128) {
+    return err!(ErrorCode::TradingEnded);
   }
 
-  if assets >= pool.settings.max_assets_in {
-    return err!(ErrorCode::MaxAssetsInExceeded);
+  if !pool.settings.selling_allowed {
+    return err!(ErrorCode::SellingDisallowed);
   }
 
-  let total_purchased_before = pool.total_purchased;
+  let assets_decimals = ctx.accounts.pool_assets_mint.decimals;
+  let shares_decimals = ctx.accounts.pool_shares_mint.decimals;
+  // Transfer fee mints withhold part of the payout, gross it up so the seller
+  // receives exactly `assets_out`
+  let assets_sent = assets_out.safe_add(get_inverse_transfer_fee(&ctx.accounts.pool_assets_mint.to_account_info(), assets_out)?)?;
+  let shares_swapped: u64 = preview_shares_in(pool, assets_sent, assets, shares, assets_decimals, shares_decimals)?;
+  check_sell_payout(pool, assets, assets_sent)?;
+
+  // The exact-in path takes the fee out of `shares_in`, gross up so selling the same
+  // `shares_in` there swaps at least `shares_swapped`
+  let shares_in = gross_up_rate(shares_swapped, factory_setting.swap_fee)?;
+  let swap_fee = shares_in.safe_sub(shares_swapped)?;
+  pool.total_swap_fees_share = pool.total_swap_fees_share.safe_add(swap_fee)?;
 
-  if total_purchased_before >= pool.settings.max_shares_out || total_purchased_before > shares {
-    return err!(ErrorCode::MaxSharesExceeded);
+  if shares_in > max_shares_in {
+    return err!(ErrorCode::SlippageExceeded);
   }
 
-  buyer_stats.purchased -= shares_in;
-
-  pool.total_purchased = total_purchased_before - shares_in;
+  // Sellers can only sell the shares they bought and haven't redeemed, the
+  // swap fee part stays in the vault for the fee recipient
+  remove_sold_shares(pool, buyer_stats, shares_in)?;
 
   let seeds = &[
     b"asset".as_ref(),
@@ -133,23 +144,26 @@ pub fn handler(
   ];
   let signer = &[&seeds[..]];
 
-  token::transfer(
+  token_interface::transfer_checked(
     CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
-        Transfer {
+        TransferChecked {
             from: ctx.accounts.pool_assets_account.to_account_info(),
-            to: ctx.accounts.depositor_assets_account.to_account_info(),
+            mint: ctx.accounts.pool_assets_mint.to_account_info(),
+            to: ctx.accounts.recipient_assets_account.to_account_info(),
             authority: ctx.accounts.asset_vault_authority.to_account_info(),
         },
         signer,
     ),
-    assets_out,
+    assets_sent,
+    ctx.accounts.pool_assets_mint.decimals,
   )?;
 
   emit!(Sell {
     caller: *ctx.accounts.depositor.to_account_info().key,
+    recipient,
     shares: shares_in,
-    assets: assets_out,
+    assets: assets_sent,
     swap_fee: swap_fee
   });
 
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
 
 #[derive(Accounts)]
 pub struct TransferOwnership<'info> {
@@ -10,6 +12,18 @@ pub struct TransferOwnership<'info> {
 
 pub fn handler(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
   let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
-  lbp_factory_setting.authority = new_owner;
+
+  if new_owner == Pubkey::default() {
+    return err!(ErrorCode::InvalidAuthority);
+  }
+
+  // The new owner has to accept before the authority changes
+  lbp_factory_setting.pending_authority = new_owner;
+
+  emit!(OwnershipTransferStarted {
+    previous_authority: lbp_factory_setting.authority,
+    new_authority: new_owner,
+  });
+
   Ok(())
-}
\ No newline at end of file
+}
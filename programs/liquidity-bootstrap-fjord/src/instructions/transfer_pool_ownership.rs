@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+
+#[derive(Accounts)]
+pub struct TransferPoolOwnership<'info> {
+
+  #[account(mut)]
+  pub pool: Box<Account<'info, Pool>>,
+
+  pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<TransferPoolOwnership>, new_owner: Pubkey) -> Result<()> {
+  let pool = &mut ctx.accounts.pool;
+
+  pool.require_role(ctx.accounts.authority.key, PoolRole::Owner)?;
+
+  if new_owner == Pubkey::default() {
+    return err!(ErrorCode::InvalidAuthority);
+  }
+
+  // The new owner has to accept before proceeds are redirected
+  pool.pending_owner = new_owner;
+
+  emit!(PoolOwnershipTransferStarted {
+    pool: pool.key(),
+    previous_owner: pool.owner,
+    new_owner,
+  });
+
+  Ok(())
+}
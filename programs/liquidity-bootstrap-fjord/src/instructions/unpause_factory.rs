@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct UnpauseFactory<'info> {
+  #[account(mut, has_one = authority)]
+  pub lbp_factory_setting: Account<'info, LBPFactorySetting>,
+  pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UnpauseFactory>) -> Result<()> {
+  let lbp_factory_setting = &mut ctx.accounts.lbp_factory_setting;
+
+  lbp_factory_setting.paused = false;
+
+  emit!(FactoryUnpaused {
+    lbp_factory: lbp_factory_setting.key(),
+  });
+
+  Ok(())
+}
@@ -13,20 +13,59 @@ pub struct UnpausePool<'info> {
   pub authority: Signer<'info>,
 }
 
-pub fn handler(ctx: Context<UnpausePool>) -> Result<()> {
+pub fn handler(ctx: Context<UnpausePool>, reason: Option<u8>) -> Result<()> {
   let pool = &mut ctx.accounts.pool;
 
-  if pool.owner != *ctx.accounts.authority.key {
-    return err!(ErrorCode::Unauthorized);
-  }
+  pool.require_role(ctx.accounts.authority.key, PoolRole::Operator)?;
 
   if pool.closed {
     return err!(ErrorCode::PoolIsClosed);
   }
 
-  if ctx.accounts.pool.paused {
-    ctx.accounts.pool.paused = false;
+  if !pool.paused {
+    return Ok(());
   }
 
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
+  let now = unix_timestamp as u64;
+
+  let paused_seconds = now.saturating_sub(pool.paused_at);
+  pool.total_paused_seconds = pool.total_paused_seconds.safe_add(paused_seconds)?;
+
+  let extension = pool.pending_sale_extension(now);
+  extend_sale(&mut pool.settings, extension)?;
+
+  pool.paused = false;
+  pool.paused_at = 0;
+
+  emit!(PoolUnpaused {
+    pool: pool.key(),
+    signer: ctx.accounts.authority.key(),
+    timestamp: unix_timestamp,
+    reason,
+    paused_seconds,
+    sale_start: pool.settings.sale_start,
+    sale_end: pool.settings.sale_end,
+  });
+
   Ok(())
-}
\ No newline at end of file
+}
+
+/// Pushes the sale window back by `extension` seconds, so the weight curve resumes where it was paused.
+/// A vesting schedule starting after the sale moves along so the cliff stays after sale end.
+fn extend_sale(settings: &mut PoolSettings, extension: u64) -> Result<()> {
+  if extension == 0 {
+    return Ok(());
+  }
+
+  if settings.sale_end < settings.vest_end {
+    settings.vest_cliff = settings.vest_cliff.safe_add(extension)?;
+    settings.vest_end = settings.vest_end.safe_add(extension)?;
+  }
+  settings.sale_start = settings.sale_start.safe_add(extension)?;
+  settings.sale_end = settings.sale_end.safe_add(extension)?;
+  Ok(())
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use crate::errors::ErrorCode;
+use crate::instructions::validate_pool_settings;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct UpdatePoolSettings<'info> {
+
+  #[account(mut)]
+  pub pool: Box<Account<'info, Pool>>,
+
+  #[account(
+    constraint = asset_vault.key() == pool.asset_vault,
+  )]
+  pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+  pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdatePoolSettings>, settings: PoolSettings) -> Result<()> {
+  let pool = &mut ctx.accounts.pool;
+
+  pool.require_role(ctx.accounts.authority.key, PoolRole::Owner)?;
+
+  let unix_timestamp = match Clock::get() {
+    Ok(clock) => clock.unix_timestamp,
+    Err(_) => return err!(ErrorCode::ClockError),
+  };
+
+  if (unix_timestamp as i128) >= (pool.settings.sale_start as i128) {
+    return err!(ErrorCode::SettingsUpdateDisallowed);
+  }
+
+  // Moving the start to now or earlier would open trading without going through the schedule
+  require!((settings.sale_start as i128) > (unix_timestamp as i128), ErrorCode::SettingsUpdateDisallowed);
+
+  // The vaults are bound to the mints the pool was created with
+  if settings.asset != pool.settings.asset || settings.share != pool.settings.share {
+    return err!(ErrorCode::InvalidAssetOrShare);
+  }
+
+  validate_pool_settings(&settings, ctx.accounts.asset_vault.amount)?;
+
+  emit!(PoolSettingsUpdated {
+    pool: pool.key(),
+    old_settings: pool.settings.clone(),
+    new_settings: settings.clone(),
+  });
+
+  pool.settings = settings;
+
+  Ok(())
+}
@@ -26,8 +26,9 @@ pub mod liquidity_bootstrap_fjord {
         platform_fee: u64,
         referrer_fee: u64,
         swap_fee: u64,
+        close_grace_period: u64,
     ) -> Result<()> {
-        instructions::initialize::handler(ctx, id, fee_recipient, platform_fee, referrer_fee, swap_fee)
+        instructions::initialize::handler(ctx, id, fee_recipient, platform_fee, referrer_fee, swap_fee, close_grace_period)
     }
 
     pub fn set_swap_fee(ctx: Context<SetSwapFee>, new_fee: u64) -> Result<()> {
@@ -46,40 +47,78 @@ pub mod liquidity_bootstrap_fjord {
         instructions::set_platform_fee::handler(ctx, new_fee)
     }
 
+    pub fn set_close_grace_period(ctx: Context<SetCloseGracePeriod>, close_grace_period: u64) -> Result<()> {
+        instructions::set_close_grace_period::handler(ctx, close_grace_period)
+    }
+
     pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
         instructions::transfer_ownership::handler(ctx, new_owner)
     }
 
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        instructions::accept_ownership::handler(ctx)
+    }
+
+    pub fn cancel_ownership_transfer(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+        instructions::cancel_ownership_transfer::handler(ctx)
+    }
+
+    pub fn pause_factory(ctx: Context<PauseFactory>) -> Result<()> {
+        instructions::pause_factory::handler(ctx)
+    }
+
+    pub fn unpause_factory(ctx: Context<UnpauseFactory>) -> Result<()> {
+        instructions::unpause_factory::handler(ctx)
+    }
+
     pub fn create_pool(
         ctx: Context<CreatePool>, 
         settings: PoolSettings, 
         id: u64, 
         shares: u64, 
         assets: u64,
-        share_vault_nonce: u8,
-        asset_vault_nonce: u8,
     ) -> Result<()> {
-        instructions::create_pool::handler(ctx, settings, id, shares, assets, share_vault_nonce, asset_vault_nonce)
+        instructions::create_pool::handler(ctx, settings, id, shares, assets)
+    }
+
+    pub fn update_pool_settings(ctx: Context<UpdatePoolSettings>, settings: PoolSettings) -> Result<()> {
+        instructions::update_pool_settings::handler(ctx, settings)
+    }
+
+    pub fn transfer_pool_ownership(ctx: Context<TransferPoolOwnership>, new_owner: Pubkey) -> Result<()> {
+        instructions::transfer_pool_ownership::handler(ctx, new_owner)
+    }
+
+    pub fn accept_pool_ownership(ctx: Context<AcceptPoolOwnership>) -> Result<()> {
+        instructions::accept_pool_ownership::handler(ctx)
+    }
+
+    pub fn set_pool_operator(ctx: Context<SetPoolOperator>, operator: Pubkey) -> Result<()> {
+        instructions::set_pool_operator::handler(ctx, operator)
     }
 
     pub fn create_user_stats(ctx: Context<CreateUserStats>) -> Result<()> {
         instructions::create_user_stats::handler(ctx)
     }
 
+    pub fn create_referrer_stats(ctx: Context<CreateReferrerStats>) -> Result<()> {
+        instructions::create_referrer_stats::handler(ctx)
+    }
+
     pub fn preview_assets_in(ctx: Context<PreviewAssetsIn>, shares_out: u64) -> Result<u64> {
         instructions::preview_assets_in::handler(ctx, shares_out)
     }
 
-    pub fn swap_assets_for_exact_shares(ctx: Context<SwapAssetsForExactShares>, recipient: Pubkey, shares_out: u64, max_assets_in: u64) -> Result<u64> {
-        instructions::swap_assets_for_exact_shares::handler(ctx, recipient, shares_out, max_assets_in)
+    pub fn swap_assets_for_exact_shares(ctx: Context<SwapAssetsForExactShares>, recipient: Pubkey, shares_out: u64, max_assets_in: u64, referrer: Option<Pubkey>, allowlist: Option<AllowlistProof>) -> Result<u64> {
+        instructions::swap_assets_for_exact_shares::handler(ctx, recipient, shares_out, max_assets_in, referrer, allowlist)
     }
 
     pub fn preview_shares_out(ctx: Context<PreviewSharesOut>, assets_in: u64) -> Result<u64> {
         instructions::preview_shares_out::handler(ctx, assets_in)
     }
 
-    pub fn swap_exact_assets_for_shares(ctx: Context<SwapExactAssetsForShares>, recipient: Pubkey, assets_in: u64, min_shares_out: u64) -> Result<u64> {
-        instructions::swap_exact_assets_for_shares::handler(ctx, recipient, assets_in, min_shares_out)
+    pub fn swap_exact_assets_for_shares(ctx: Context<SwapExactAssetsForShares>, recipient: Pubkey, assets_in: u64, min_shares_out: u64, referrer: Option<Pubkey>, allowlist: Option<AllowlistProof>) -> Result<u64> {
+        instructions::swap_exact_assets_for_shares::handler(ctx, recipient, assets_in, min_shares_out, referrer, allowlist)
     }
 
     pub fn preview_assets_out(ctx: Context<PreviewAssetsOut>, shares_in: u64) -> Result<u64> {
@@ -98,21 +137,33 @@ pub mod liquidity_bootstrap_fjord {
         instructions::swap_shares_for_exact_assets::handler(ctx, recipient, assets_out, max_shares_in)
     }
 
-    pub fn redeem(ctx: Context<Redeem>) -> Result<()> {
-        instructions::redeem::handler(ctx)
+    pub fn redeem(ctx: Context<Redeem>, amount: Option<u64>, recipient: Option<Pubkey>) -> Result<()> {
+        instructions::redeem::handler(ctx, amount, recipient)
+    }
+
+    pub fn redeem_for<'info>(ctx: Context<'_, '_, 'info, 'info, RedeemFor<'info>>) -> Result<()> {
+        instructions::redeem_for::handler(ctx)
+    }
+
+    pub fn preview_redeem(ctx: Context<PreviewRedeem>) -> Result<PreviewRedeemOutput> {
+        instructions::preview_redeem::handler(ctx)
     }
 
     pub fn close(ctx: Context<Close>) -> Result<()> {
         instructions::close::handler(ctx)
     }
 
-    pub fn pause_pool(ctx: Context<PausePool>) -> Result<()> {
-        instructions::pause_pool::handler(ctx)
+    pub fn claim_referrer_fees(ctx: Context<ClaimReferrerFees>) -> Result<()> {
+        instructions::claim_referrer_fees::handler(ctx)
+    }
+
+    pub fn pause_pool(ctx: Context<PausePool>, reason: Option<u8>) -> Result<()> {
+        instructions::pause_pool::handler(ctx, reason)
     }
 
 
-    pub fn unpause_pool(ctx: Context<UnpausePool>) -> Result<()> {
-        instructions::unpause_pool::handler(ctx)
+    pub fn unpause_pool(ctx: Context<UnpausePool>, reason: Option<u8>) -> Result<()> {
+        instructions::unpause_pool::handler(ctx, reason)
     }
 
     pub fn compute_reserves_and_weights(ctx: Context<ComputeReservesAndWeights>) -> Result<ComputeReservesAndWeightsOutput> {
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+/// Merkle proof that `(recipient, max_allocation)` is a leaf of the pool allowlist.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowlistProof {
+  pub max_allocation: u64, // shares the recipient may purchase in total
+  pub proof: Vec<[u8; 32]>,
+}
@@ -4,9 +4,12 @@ use anchor_lang::prelude::*;
 pub struct LBPFactorySetting {
   pub id: u64, // 8
   pub authority: Pubkey, // 32
+  pub pending_authority: Pubkey, // 32 -> default pubkey when no transfer is pending
   pub fee_recipient: Pubkey, // 32
   pub platform_fee: u64, // 8
   pub referrer_fee: u64, // 8
   pub swap_fee: u64, // 8
+  pub close_grace_period: u64, // 8 -> seconds after sale end when anyone can close a pool
+  pub paused: bool, // 1 -> halts trading on every pool of the factory
   pub bump: u8, // 1
 }
\ No newline at end of file
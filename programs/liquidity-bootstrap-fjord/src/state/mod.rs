@@ -1,8 +1,15 @@
-
 mod lbp_factory_setting;
 mod pool;
 mod user_stats;
+mod referrer_stats;
+mod weight_curve;
+mod allowlist;
+mod vesting_schedule;
 
 pub use lbp_factory_setting::*;
 pub use pool::*;
 pub use user_stats::*;
+pub use referrer_stats::*;
+pub use weight_curve::*;
+pub use allowlist::*;
+pub use vesting_schedule::*;
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use super::{UserStats, VestingSchedule, WeightCurve};
+use crate::errors::ErrorCode;
+use crate::utils::SafeMath;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PoolSettings {
@@ -6,9 +9,11 @@ pub struct PoolSettings {
   pub share: Pubkey, // 32
   pub virtual_assets: u64, // 8
   pub virtual_shares: u64, // 8
-  pub max_share_price: u64, // 8
+  pub max_share_price: u64, // 8 -> whole assets per whole share, 1e9 precision
   pub max_shares_out: u64, // 8
   pub max_assets_in: u64, // 8
+  pub max_percentage_in: u64, // 8 -> largest share of the input reserve a single swap may put in, 1e9 precision
+  pub max_percentage_out: u64, // 8 -> largest share of the output reserve a single swap may take out, 1e9 precision
   pub weight_start: u64, // 8
   pub weight_end: u64, // 8
   pub sale_start: u64, // 8
@@ -16,25 +21,109 @@ pub struct PoolSettings {
   pub vest_cliff: u64, // 8
   pub vest_end: u64, // 8
   pub selling_allowed: bool, // 1
+  pub extend_sale_on_pause: bool, // 1 -> push the sale window back by the time paused during the sale
+  pub whitelist_merkle_root: Option<[u8; 32]>, // 33 -> buys need an allowlist proof when set
+  pub max_shares_per_user: Option<u64>, // 9
+  pub max_assets_per_user: Option<u64>, // 9
+  pub min_assets_per_buy: Option<u64>, // 9
+  pub weight_curve: WeightCurve, // WeightCurve::LEN
+  pub vesting_schedule: VestingSchedule, // VestingSchedule::LEN
 }
 
 #[account]
 pub struct Pool {
   pub id: u64, // 8
   pub owner: Pubkey, // 32
+  pub pending_owner: Pubkey, // 32 -> default pubkey when no transfer is pending
+  pub operator: Pubkey, // 32 -> default pubkey when no operator is set
   pub lbp_factory: Pubkey, // 32
   pub settings: PoolSettings,
-  pub initialized: bool, // 1
   pub closed: bool, // 1
   pub paused: bool, // 1
+  pub paused_at: u64, // 8 -> timestamp of the current pause, 0 when not paused
+  pub total_paused_seconds: u64, // 8
   pub total_swap_fees_asset: u64, // 8
   pub total_swap_fees_share: u64, // 8
   pub total_purchased: u64, // 8
+  pub total_referred: u64, // 8
   pub share_vault_nonce: u8, // 1
   pub asset_vault_nonce: u8, // 1
   pub share_vault_authority: Pubkey, // 32
   pub asset_vault_authority: Pubkey, // 32
   pub share_vault: Pubkey, // 32
   pub asset_vault: Pubkey, // 32
+  pub bump: u8, // 1
 }
 
+pub const POOL_SEED: &[u8] = b"pool";
+
+impl Pool {
+  // settings take 2 * 32 + 13 * 8 + 1 + 1 + 33 + 3 * 9 + WeightCurve::LEN + VestingSchedule::LEN bytes
+  pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 230 + WeightCurve::LEN + VestingSchedule::LEN + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 32 + 32 + 32 + 32 + 1;
+
+  /// Returns whether `signer` holds `role` on this pool, the owner holds every role.
+  pub fn has_role(&self, signer: &Pubkey, role: PoolRole) -> bool {
+    if *signer == self.owner {
+      return true;
+    }
+    match role {
+      PoolRole::Owner => false,
+      PoolRole::Operator => self.operator != Pubkey::default() && *signer == self.operator,
+    }
+  }
+
+  /// Authorization check shared by the pool admin instructions.
+  pub fn require_role(&self, signer: &Pubkey, role: PoolRole) -> Result<()> {
+    if !self.has_role(signer, role) {
+      return err!(ErrorCode::Unauthorized);
+    }
+    Ok(())
+  }
+
+  /// Seconds the sale gets back for the current pause when `extend_sale_on_pause` is set:
+  /// everything from the later of the pause start and sale start until `now`, as long as the
+  /// pause started before sale end, so buyers keep the sale time that was left when it began.
+  pub fn pending_sale_extension(&self, now: u64) -> u64 {
+    if !self.paused || !self.settings.extend_sale_on_pause || self.paused_at >= self.settings.sale_end {
+      return 0;
+    }
+    now.saturating_sub(self.paused_at.max(self.settings.sale_start))
+  }
+}
+
+/// Roles allowed to administer a pool. Operators can pause and unpause but
+/// never receive proceeds, those always go to the owner.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PoolRole {
+  Owner,
+  Operator,
+}
+
+/// Derives the canonical address of the pool `id` created under `lbp_factory`.
+pub fn find_pool_address(lbp_factory: &Pubkey, id: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[POOL_SEED, lbp_factory.as_ref(), &id.to_le_bytes()], &crate::ID)
+}
+
+
+/// Removes `shares` sold back to the pool from the seller's unclaimed balance and the pool total.
+pub fn remove_sold_shares(pool: &mut Pool, seller_stats: &mut UserStats, shares: u64) -> Result<()> {
+  let unclaimed = seller_stats.purchased.safe_sub(seller_stats.claimed)?;
+  if shares > unclaimed {
+    return err!(ErrorCode::InsufficientShares);
+  }
+  seller_stats.purchased = seller_stats.purchased.safe_sub(shares)?;
+  pool.total_purchased = pool.total_purchased.safe_sub(shares)?;
+  Ok(())
+}
+
+/// Fails when paying `assets_out` to a seller would dip into the swap and referrer fees owed out of
+/// the `assets` held in the asset vault, virtual assets can price a sell above what the vault holds.
+pub fn check_sell_payout(pool: &Pool, assets: u64, assets_out: u64) -> Result<()> {
+  let available = assets
+    .checked_sub(pool.total_swap_fees_asset.safe_add(pool.total_referred)?)
+    .ok_or(error!(ErrorCode::InsufficientReserve))?;
+  if assets_out > available {
+    return err!(ErrorCode::InsufficientReserve);
+  }
+  Ok(())
+}
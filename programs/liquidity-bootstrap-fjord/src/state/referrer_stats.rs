@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ReferrerStats {
+  // Like UserStats, the pool and referrer are part of the pda seeds and are not stored
+  pub accrued: u64, // 8 -> referral fees earned in assets
+  pub claimed: u64, // 8
+  pub bump: u8, // 1
+}
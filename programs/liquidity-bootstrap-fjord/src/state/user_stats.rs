@@ -5,5 +5,6 @@ pub struct UserStats {
   // Note that we don't need to store user and pool because they already in pda, including this allows attackers set them arbitrarily
   pub purchased: u64, // 8 -> this is the purchased shares
   pub claimed: u64, // 8
+  pub assets_spent: u64, // 8 -> assets the pool received for this user's buys, sells don't reduce it
   pub bump: u8, // 1
 }
\ No newline at end of file
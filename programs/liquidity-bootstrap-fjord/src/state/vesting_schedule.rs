@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+/// Maximum number of tranches of a tranche release.
+pub const MAX_VESTING_TRANCHES: usize = 8;
+
+/// Basis points of a whole purchase.
+pub const MAX_BPS: u16 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VestingTranche {
+  pub offset: u64, // 8 -> seconds since vest cliff
+  pub bps: u16, // 2 -> share of the purchase unlocked at the offset
+}
+
+/// How the shares left after the initial unlock are released between `vest_cliff` and `vest_end`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum VestingRelease {
+  /// Continuously from the cliff until vest end.
+  Linear,
+  /// Equal steps every `period` seconds after the cliff, whatever is left unlocks at vest end.
+  Periodic { period: u64 },
+  /// Each tranche unlocks its basis points at its offset, together with the initial unlock they add up to the whole purchase.
+  Tranches { tranches: Vec<VestingTranche> },
+}
+
+/// Unlock schedule of purchased shares, only used when the pool vests (`sale_end < vest_end`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct VestingSchedule {
+  pub initial_unlock_bps: u16, // 2 -> unlocked as soon as the pool closes
+  pub release: VestingRelease, // VestingRelease::LEN
+}
+
+impl VestingRelease {
+  // variant tag, vec length and the largest variant payload
+  pub const LEN: usize = 1 + 4 + MAX_VESTING_TRANCHES * 10;
+}
+
+impl VestingSchedule {
+  pub const LEN: usize = 2 + VestingRelease::LEN;
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// Maximum number of breakpoints of a piecewise linear curve.
+pub const MAX_WEIGHT_BREAKPOINTS: usize = 8;
+
+/// Bounds for the step count of a step curve.
+pub const MIN_WEIGHT_STEPS: u8 = 2;
+pub const MAX_WEIGHT_STEPS: u8 = 100;
+
+/// Upper bound for the decay rate of an exponential decay curve, 20.0 with 1e9 precision.
+pub const MAX_DECAY_RATE: u64 = 20_000_000_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WeightBreakpoint {
+  pub offset: u64, // 8 -> seconds since sale start
+  pub weight: u64, // 8
+}
+
+/// Shape of the asset weight between `weight_start` at sale start and `weight_end` at sale end.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum WeightCurve {
+  /// Straight line from the start to the end weight.
+  Linear,
+  /// Most of the change happens early in the sale, `decay_rate` (1e9 precision) sets how fast.
+  ExponentialDecay { decay_rate: u64 },
+  /// `steps` equally long plateaus, the first at the start weight and the last at the end weight.
+  Step { steps: u8 },
+  /// Straight lines through the start weight, each breakpoint and the end weight.
+  PiecewiseLinear { breakpoints: Vec<WeightBreakpoint> },
+}
+
+impl WeightCurve {
+  // variant tag, vec length and the largest variant payload
+  pub const LEN: usize = 1 + 4 + MAX_WEIGHT_BREAKPOINTS * 16;
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::errors::ErrorCode;
+use crate::state::{AllowlistProof, PoolSettings};
+
+/// Leaves are `sha256(recipient || max_allocation_le)`, nodes hash the sorted pair of children.
+pub fn allowlist_leaf(recipient: &Pubkey, max_allocation: u64) -> [u8; 32] {
+  hashv(&[recipient.as_ref(), &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+  let mut node = leaf;
+  for sibling in proof {
+    node = if node <= *sibling {
+      hashv(&[&node, sibling]).to_bytes()
+    } else {
+      hashv(&[sibling, &node]).to_bytes()
+    };
+  }
+  node == *root
+}
+
+/// Checks that `recipient` is on the pool allowlist and stays within its allocation
+/// once `purchased_after` shares are recorded. Pools without a root are open to anyone.
+pub fn check_allowlist(
+  settings: &PoolSettings,
+  recipient: &Pubkey,
+  purchased_after: u64,
+  allowlist: &Option<AllowlistProof>,
+) -> Result<()> {
+  let root = match &settings.whitelist_merkle_root {
+    Some(root) => root,
+    None => return Ok(()),
+  };
+
+  let allowlist = match allowlist {
+    Some(allowlist) => allowlist,
+    None => return err!(ErrorCode::InvalidAllowlistProof),
+  };
+
+  let leaf = allowlist_leaf(recipient, allowlist.max_allocation);
+  if !verify_merkle_proof(root, leaf, &allowlist.proof) {
+    return err!(ErrorCode::InvalidAllowlistProof);
+  }
+
+  if purchased_after > allowlist.max_allocation {
+    return err!(ErrorCode::AllocationExceeded);
+  }
+
+  Ok(())
+}
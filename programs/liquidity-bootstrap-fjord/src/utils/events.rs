@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::PoolSettings;
 
 // Factory related Events
 
@@ -27,8 +28,87 @@ pub struct FeeRecipientSet {
   pub fee_recipient: Pubkey
 }
 
+#[event]
+pub struct CloseGracePeriodSet {
+  pub close_grace_period: u64
+}
+
+#[event]
+pub struct OwnershipTransferStarted {
+  pub previous_authority: Pubkey,
+  pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+  pub previous_authority: Pubkey,
+  pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferCancelled {
+  pub authority: Pubkey,
+  pub cancelled_authority: Pubkey,
+}
+
+#[event]
+pub struct FactoryPaused {
+  pub lbp_factory: Pubkey,
+}
+
+#[event]
+pub struct FactoryUnpaused {
+  pub lbp_factory: Pubkey,
+}
+
 
 // Pool related Events
+#[event]
+pub struct PoolSettingsUpdated {
+  pub pool: Pubkey,
+  pub old_settings: PoolSettings,
+  pub new_settings: PoolSettings,
+}
+
+#[event]
+pub struct PoolOwnershipTransferStarted {
+  pub pool: Pubkey,
+  pub previous_owner: Pubkey,
+  pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct PoolOwnershipTransferred {
+  pub pool: Pubkey,
+  pub previous_owner: Pubkey,
+  pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct PoolOperatorSet {
+  pub pool: Pubkey,
+  pub operator: Pubkey,
+}
+
+#[event]
+pub struct PoolPaused {
+  pub pool: Pubkey,
+  pub signer: Pubkey,
+  pub timestamp: i64,
+  pub reason: Option<u8>,
+}
+
+#[event]
+pub struct PoolUnpaused {
+  pub pool: Pubkey,
+  pub signer: Pubkey,
+  pub timestamp: i64,
+  pub reason: Option<u8>,
+  pub paused_seconds: u64,
+  pub sale_start: u64,
+  pub sale_end: u64,
+}
+
 #[event]
 pub struct Buy {
   pub caller: Pubkey,
@@ -41,21 +121,38 @@ pub struct Buy {
 #[event]
 pub struct Sell {
   pub caller: Pubkey,
+  pub recipient: Pubkey,
   pub shares: u64,
   pub assets: u64,
   pub swap_fee: u64,
 }
 
+#[event]
+pub struct ReferrerFeeAccrued {
+  pub referrer: Pubkey,
+  pub assets: u64,
+}
+
+#[event]
+pub struct ReferrerFeesClaimed {
+  pub referrer: Pubkey,
+  pub assets: u64,
+}
+
 #[event]
 pub struct ClosePool {
+  pub closer: Pubkey,
   pub platform_fees: u64,
+  pub referrer_fees: u64,
   pub swap_fees_asset: u64,
   pub swap_fees_share: u64,
 }
 
 #[event]
 pub struct Redeemed {
-  pub recipient: Pubkey,
+  pub user: Pubkey,
+  pub recipient: Pubkey, // share token account the shares were sent to
+  pub custom_recipient: bool, // the user overrode their ATA as destination
   pub shares: u64,
   pub total_claimed: u64,
 }
\ No newline at end of file
@@ -0,0 +1,317 @@
+mod u256 {
+  // the macro expansion trips lints we can't fix from here
+  #![allow(clippy::manual_div_ceil, clippy::assign_op_pattern)]
+  use uint::construct_uint;
+
+  construct_uint! {
+    pub struct U256(4);
+  }
+}
+
+pub use u256::U256;
+
+/// 1.0 in 18 decimal fixed point.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// ln(2) scaled by 1e36, truncated and rounded up.
+const LN2_E36_DOWN: u128 = 693_147_180_559_945_309_417_232_121_458_176_568;
+const LN2_E36_UP: u128 = 693_147_180_559_945_309_417_232_121_458_176_569;
+
+/// Number of atanh terms evaluated by `ln_wad`. The mantissa is reduced to [1, 2),
+/// so the series argument is at most 1/3 and the tail after this many terms is
+/// below 1e-21.
+const LN_SERIES_TERMS: u128 = 20;
+
+/// Number of Taylor terms evaluated by `exp_wad`. The argument is reduced below
+/// ln(2), so the tail after this many terms is below 1e-25.
+const EXP_SERIES_TERMS: u128 = 24;
+
+/// Largest gap, in WAD units, between the `Down` and `Up` results of `ln_wad`.
+pub const LN_MAX_ERROR: u128 = 100;
+
+/// Largest gap between the `Down` and `Up` results of `exp_wad`, in parts per 1e18
+/// of the result.
+pub const EXP_MAX_RELATIVE_ERROR: u128 = 50;
+
+/// Direction every intermediate result is rounded in. `Down` produces a lower
+/// bound of the exact value, `Up` an upper bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+  Down,
+  Up,
+}
+
+impl Rounding {
+  pub fn opposite(self) -> Rounding {
+    match self {
+      Rounding::Down => Rounding::Up,
+      Rounding::Up => Rounding::Down,
+    }
+  }
+}
+
+/// `a * b / denominator` with a 256 bit intermediate. Returns `None` on division
+/// by zero or when the result does not fit in a u128.
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Option<u128> {
+  if denominator == 0 {
+    return None;
+  }
+  let (mut quotient, remainder) = (U256::from(a) * U256::from(b)).div_mod(U256::from(denominator));
+  if rounding == Rounding::Up && !remainder.is_zero() {
+    quotient += U256::one();
+  }
+  if quotient > U256::from(u128::MAX) {
+    return None;
+  }
+  Some(quotient.as_u128())
+}
+
+pub fn mul_wad(a: u128, b: u128, rounding: Rounding) -> Option<u128> {
+  mul_div(a, b, WAD, rounding)
+}
+
+pub fn div_wad(a: u128, b: u128, rounding: Rounding) -> Option<u128> {
+  mul_div(a, WAD, b, rounding)
+}
+
+fn div_rounding(a: u128, b: u128, rounding: Rounding) -> u128 {
+  match rounding {
+    Rounding::Down => a / b,
+    Rounding::Up => a.div_ceil(b),
+  }
+}
+
+fn shr_rounding(a: u128, shift: u32, rounding: Rounding) -> u128 {
+  let shifted = a >> shift;
+  if rounding == Rounding::Up && shifted << shift != a {
+    shifted + 1
+  } else {
+    shifted
+  }
+}
+
+/// Natural logarithm of `x` for `x >= 1.0`.
+///
+/// `x` is reduced to `m * 2^k` with `m` in [1, 2) and `ln(m)` is evaluated as
+/// `2 * atanh((m - 1) / (m + 1))`. The exact result lies between the `Down` and
+/// `Up` results, which are at most `LN_MAX_ERROR` apart.
+pub fn ln_wad(x: u128, rounding: Rounding) -> Option<u128> {
+  if x < WAD {
+    return None;
+  }
+
+  let k: u32 = 127 - (x / WAD).leading_zeros();
+  let m = shr_rounding(x, k, rounding);
+
+  let s = mul_div(m - WAD, WAD, m + WAD, rounding)?;
+  let s_squared = mul_wad(s, s, rounding)?;
+
+  let mut sum = s;
+  let mut term = s;
+  for n in 1..=LN_SERIES_TERMS {
+    term = mul_wad(term, s_squared, rounding)?;
+    if term == 0 {
+      break;
+    }
+    sum += div_rounding(term, 2 * n + 1, rounding);
+  }
+  if rounding == Rounding::Up {
+    // truncated tail of the series
+    sum += 1;
+  }
+
+  let ln2 = match rounding {
+    Rounding::Down => LN2_E36_DOWN,
+    Rounding::Up => LN2_E36_UP,
+  };
+  let k_ln2 = mul_div(k as u128, ln2, WAD, rounding)?;
+
+  (2 * sum).checked_add(k_ln2)
+}
+
+/// `e^x` for `x >= 0`.
+///
+/// `x` is reduced to `k * ln(2) + r` with `r` in [0, ln(2)] and `e^r` is evaluated
+/// with its Taylor series before shifting by `k`. The exact result lies between the
+/// `Down` and `Up` results, which are at most `EXP_MAX_RELATIVE_ERROR` parts per
+/// 1e18 apart. Returns `None` when the result does not fit in a u128.
+pub fn exp_wad(x: u128, rounding: Rounding) -> Option<u128> {
+  let x_e36 = x.checked_mul(WAD)?;
+
+  // Dividing by the larger ln(2) bound keeps r non-negative in both directions
+  let k = x_e36 / LN2_E36_UP;
+  if k >= 128 {
+    return None;
+  }
+  let r_e36 = match rounding {
+    Rounding::Down => x_e36 - k * LN2_E36_UP,
+    Rounding::Up => x_e36 - k * LN2_E36_DOWN,
+  };
+  let r = div_rounding(r_e36, WAD, rounding);
+
+  let mut sum = WAD + r;
+  let mut term = r;
+  for n in 2..=EXP_SERIES_TERMS {
+    term = mul_div(term, r, n * WAD, rounding)?;
+    if term == 0 {
+      break;
+    }
+    sum += term;
+  }
+  if rounding == Rounding::Up {
+    // truncated tail of the series
+    sum += 1;
+  }
+
+  let k = k as u32;
+  if sum > u128::MAX >> k {
+    return None;
+  }
+  Some(sum << k)
+}
+
+/// `base^exponent` for `base >= 1.0`, computed as `e^(exponent * ln(base))`.
+pub fn pow_wad(base: u128, exponent: u128, rounding: Rounding) -> Option<u128> {
+  let ln_base = ln_wad(base, rounding)?;
+  let y = mul_wad(ln_base, exponent, rounding)?;
+  exp_wad(y, rounding)
+}
+
+/// `base^-exponent` for `base >= 1.0`.
+pub fn inv_pow_wad(base: u128, exponent: u128, rounding: Rounding) -> Option<u128> {
+  let power = pow_wad(base, exponent, rounding.opposite())?;
+  mul_div(WAD, WAD, power, rounding)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /* High precision reference, every value is scaled by 1e36 and truncated */
+
+  const E36: u128 = WAD * WAD;
+
+  fn e36() -> U256 {
+    U256::from(E36)
+  }
+
+  fn ref_two_atanh(m: U256) -> U256 {
+    let s = (m - e36()) * e36() / (m + e36());
+    let s_squared = s * s / e36();
+    let mut sum = s;
+    let mut term = s;
+    let mut n = 1u64;
+    loop {
+      term = term * s_squared / e36();
+      if term.is_zero() {
+        break;
+      }
+      sum += term / U256::from(2 * n + 1);
+      n += 1;
+    }
+    sum * U256::from(2)
+  }
+
+  fn ref_ln2() -> U256 {
+    ref_two_atanh(e36() * U256::from(2))
+  }
+
+  fn ref_ln(x: u128) -> U256 {
+    let mut m = U256::from(x) * U256::from(WAD);
+    let mut k = 0u64;
+    while m >= e36() * U256::from(2) {
+      m /= U256::from(2);
+      k += 1;
+    }
+    ref_ln2() * U256::from(k) + ref_two_atanh(m)
+  }
+
+  fn ref_exp(x: u128) -> U256 {
+    let y = U256::from(x) * U256::from(WAD);
+    let ln2 = ref_ln2();
+    let k = y / ln2;
+    let r = y - ln2 * k;
+    let mut sum = e36() + r;
+    let mut term = r;
+    let mut n = 2u64;
+    loop {
+      term = term * r / (e36() * U256::from(n));
+      if term.is_zero() {
+        break;
+      }
+      sum += term;
+      n += 1;
+    }
+    sum << k.as_usize()
+  }
+
+  // the reference truncates a few dozen times at 1e-36, far below the WAD precision checked here
+  fn slack(reference: U256) -> U256 {
+    reference / U256::from(10u128.pow(30)) + U256::from(1_000)
+  }
+
+  fn assert_brackets(name: &str, input: u128, down: u128, up: u128, reference: U256) {
+    let down_e36 = U256::from(down) * U256::from(WAD);
+    let up_e36 = U256::from(up) * U256::from(WAD);
+    assert!(down_e36 <= reference + slack(reference), "{name}({input}) Down {down} above exact {reference}");
+    assert!(up_e36 + slack(reference) >= reference, "{name}({input}) Up {up} below exact {reference}");
+  }
+
+  // deterministic samples spread over every magnitude of [min, max] so failures are reproducible
+  fn samples(min: u128, max: u128, count: usize) -> Vec<u128> {
+    let mut seed: u128 = 0x2545f491;
+    let mut next = || {
+      seed = seed * 48271 % ((1 << 61) - 1);
+      seed
+    };
+    let span_bits = 128 - (max - min).leading_zeros();
+    (0..count)
+      .map(|_| {
+        let bits = (next() % span_bits as u128) as u32 + 1;
+        let value = (next() << 64 | next()) % (1u128 << bits.min(127));
+        min + value % (max - min + 1)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn ln_wad_brackets_the_exact_result() {
+    for x in samples(WAD, u128::MAX, 200).into_iter().chain([WAD + 1, 2 * WAD, 3 * WAD, u128::MAX]) {
+      let down = ln_wad(x, Rounding::Down).unwrap();
+      let up = ln_wad(x, Rounding::Up).unwrap();
+      assert_brackets("ln_wad", x, down, up, ref_ln(x));
+      assert!(up - down <= LN_MAX_ERROR, "ln_wad({x}) gap {} above LN_MAX_ERROR", up - down);
+    }
+  }
+
+  #[test]
+  fn exp_wad_brackets_the_exact_result() {
+    for x in samples(0, 46 * WAD, 200).into_iter().chain([1, WAD, 10 * WAD]) {
+      let (Some(down), Some(up)) = (exp_wad(x, Rounding::Down), exp_wad(x, Rounding::Up)) else {
+        continue;
+      };
+      assert_brackets("exp_wad", x, down, up, ref_exp(x));
+      let max_gap = mul_div(down, EXP_MAX_RELATIVE_ERROR, WAD, Rounding::Up).unwrap();
+      assert!(up - down <= max_gap, "exp_wad({x}) gap {} above EXP_MAX_RELATIVE_ERROR", up - down);
+    }
+  }
+
+  #[test]
+  fn ln_wad_edge_cases() {
+    assert_eq!(ln_wad(WAD, Rounding::Down), Some(0));
+    assert!(ln_wad(WAD, Rounding::Up).unwrap() <= LN_MAX_ERROR);
+    assert_eq!(ln_wad(WAD - 1, Rounding::Down), None);
+    assert_eq!(ln_wad(0, Rounding::Up), None);
+  }
+
+  #[test]
+  fn exp_wad_edge_cases() {
+    assert_eq!(exp_wad(0, Rounding::Down), Some(WAD));
+    let up = exp_wad(0, Rounding::Up).unwrap();
+    assert!(up >= WAD && up - WAD <= EXP_MAX_RELATIVE_ERROR);
+
+    assert_eq!(exp_wad(100 * WAD, Rounding::Down), None);
+    assert_eq!(exp_wad(100 * WAD, Rounding::Up), None);
+    assert_eq!(exp_wad(u128::MAX, Rounding::Down), None);
+  }
+}
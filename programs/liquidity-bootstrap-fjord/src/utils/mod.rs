@@ -1,5 +1,17 @@
 mod weights;
 mod events;
+mod fixed_point;
+mod token_extensions;
+mod allowlist;
+mod purchase_limits;
+mod safe_math;
+mod vesting;
 
 pub use weights::*;
-pub use events::*;
\ No newline at end of file
+pub use events::*;
+pub use fixed_point::*;
+pub use token_extensions::*;
+pub use allowlist::*;
+pub use purchase_limits::*;
+pub use safe_math::*;
+pub use vesting::*;
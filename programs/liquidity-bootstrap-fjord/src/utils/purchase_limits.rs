@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::{PoolSettings, UserStats};
+use super::safe_math::SafeMath;
+
+/// Checks the per-user limits for a buy of `shares` paid with `assets` received by the pool.
+pub fn check_purchase_limits(
+  settings: &PoolSettings,
+  user_stats: &UserStats,
+  assets: u64,
+  shares: u64,
+) -> Result<()> {
+  if let Some(min_assets_per_buy) = settings.min_assets_per_buy {
+    if assets < min_assets_per_buy {
+      return err!(ErrorCode::MinAssetsPerBuyNotMet);
+    }
+  }
+
+  if let Some(max_shares_per_user) = settings.max_shares_per_user {
+    if user_stats.purchased.safe_add(shares)? > max_shares_per_user {
+      return err!(ErrorCode::MaxSharesPerUserExceeded);
+    }
+  }
+
+  if let Some(max_assets_per_user) = settings.max_assets_per_user {
+    if user_stats.assets_spent.safe_add(assets)? > max_assets_per_user {
+      return err!(ErrorCode::MaxAssetsPerUserExceeded);
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use super::fixed_point::{mul_div, Rounding};
+use super::weights::WEIGHT_PRECISION;
+
+/// Checked `u64` arithmetic that fails with a typed error instead of panicking.
+pub trait SafeMath: Sized {
+  fn safe_add(self, rhs: Self) -> Result<Self>;
+  fn safe_sub(self, rhs: Self) -> Result<Self>;
+  fn safe_mul(self, rhs: Self) -> Result<Self>;
+  fn safe_div(self, rhs: Self) -> Result<Self>;
+}
+
+impl SafeMath for u64 {
+  fn safe_add(self, rhs: u64) -> Result<u64> {
+    self.checked_add(rhs).ok_or(error!(ErrorCode::Overflow))
+  }
+
+  fn safe_sub(self, rhs: u64) -> Result<u64> {
+    self.checked_sub(rhs).ok_or(error!(ErrorCode::Underflow))
+  }
+
+  fn safe_mul(self, rhs: u64) -> Result<u64> {
+    self.checked_mul(rhs).ok_or(error!(ErrorCode::Overflow))
+  }
+
+  fn safe_div(self, rhs: u64) -> Result<u64> {
+    self.checked_div(rhs).ok_or(error!(ErrorCode::DivisionByZero))
+  }
+}
+
+/// `amount * rate / WEIGHT_PRECISION` rounded down, `rate` has 1e9 precision.
+/// The product is taken in u128 so large amounts can not overflow.
+pub fn apply_rate(amount: u64, rate: u64) -> Result<u64> {
+  let result = amount as u128 * rate as u128 / WEIGHT_PRECISION as u128;
+  u64::try_from(result).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+/// `amount * WEIGHT_PRECISION / (WEIGHT_PRECISION - rate)` rounded up, the inverse of taking
+/// `apply_rate` out: `gross - apply_rate(gross, rate)` is never less than `amount`.
+pub fn gross_up_rate(amount: u64, rate: u64) -> Result<u64> {
+  let net_rate = WEIGHT_PRECISION.safe_sub(rate)?;
+  let result = mul_div(amount as u128, WEIGHT_PRECISION as u128, net_rate as u128, Rounding::Up)
+    .ok_or(error!(ErrorCode::DivisionByZero))?;
+  u64::try_from(result).map_err(|_| error!(ErrorCode::Overflow))
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+  self,
+  extension::{
+    transfer_fee::TransferFeeConfig,
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+  },
+  state::Mint,
+};
+use crate::errors::ErrorCode;
+
+/// Mint extensions that let a third party move or lock tokens held by the pool vaults,
+/// or that need extra accounts on every transfer.
+const UNSUPPORTED_MINT_EXTENSIONS: [ExtensionType; 3] = [
+  ExtensionType::PermanentDelegate,
+  ExtensionType::NonTransferable,
+  ExtensionType::TransferHook,
+];
+
+pub fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+  if *mint.owner != spl_token_2022::ID {
+    return Ok(());
+  }
+  let data = mint.try_borrow_data()?;
+  let state = StateWithExtensions::<Mint>::unpack(&data)?;
+  for extension in state.get_extension_types()? {
+    if UNSUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+      return err!(ErrorCode::UnsupportedMintExtension);
+    }
+  }
+  Ok(())
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+  if *mint.owner != spl_token_2022::ID {
+    return Ok(None);
+  }
+  let data = mint.try_borrow_data()?;
+  let state = StateWithExtensions::<Mint>::unpack(&data)?;
+  Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Fee withheld by the token program when `amount` is sent, the recipient gets `amount - fee`.
+pub fn get_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+  match transfer_fee_config(mint)? {
+    Some(config) => config
+      .calculate_epoch_fee(Clock::get()?.epoch, amount)
+      .ok_or(error!(ErrorCode::MathError)),
+    None => Ok(0),
+  }
+}
+
+/// Fee to add on top of `amount` so that the recipient gets exactly `amount`.
+pub fn get_inverse_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+  match transfer_fee_config(mint)? {
+    Some(config) => config
+      .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+      .ok_or(error!(ErrorCode::MathError)),
+    None => Ok(0),
+  }
+}
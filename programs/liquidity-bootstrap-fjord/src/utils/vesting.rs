@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use super::fixed_point::{mul_div, Rounding};
+use super::safe_math::*;
+
+/// Shares out of `purchased` that are unlocked at `now`, following the pool's vesting schedule.
+/// Never more than `purchased`, whatever the schedule adds up to.
+pub fn vested_shares(settings: &PoolSettings, purchased: u64, now: u64) -> Result<u64> {
+  // without a vesting period everything unlocks when the pool closes
+  if settings.sale_end >= settings.vest_end || now >= settings.vest_end {
+    return Ok(purchased);
+  }
+
+  let schedule = &settings.vesting_schedule;
+  let initial = portion(purchased, schedule.initial_unlock_bps as u64, MAX_BPS as u64)?;
+  if now < settings.vest_cliff {
+    return Ok(initial);
+  }
+
+  let remaining = purchased.saturating_sub(initial);
+  let elapsed = now.saturating_sub(settings.vest_cliff);
+  let duration = settings.vest_end.saturating_sub(settings.vest_cliff);
+  let vested = match &schedule.release {
+    VestingRelease::Linear => {
+      initial.saturating_add(portion(remaining, elapsed, duration)?)
+    }
+    VestingRelease::Periodic { period } => {
+      let elapsed_periods = elapsed.safe_div(*period)?;
+      let periods = duration.div_ceil(*period);
+      initial.saturating_add(portion(remaining, elapsed_periods, periods)?)
+    }
+    VestingRelease::Tranches { tranches } => {
+      let unlocked_bps = tranches
+        .iter()
+        .filter(|tranche| tranche.offset <= elapsed)
+        .fold(schedule.initial_unlock_bps as u64, |bps, tranche| bps.saturating_add(tranche.bps as u64));
+      portion(purchased, unlocked_bps, MAX_BPS as u64)?
+    }
+  };
+  Ok(vested.min(purchased))
+}
+
+/// Shares that can be redeemed now, saturating at zero when more than the vested amount was claimed.
+pub fn claimable_shares(settings: &PoolSettings, user_stats: &UserStats, now: u64) -> Result<u64> {
+  let vested = vested_shares(settings, user_stats.purchased, now)?;
+  Ok(vested.saturating_sub(user_stats.claimed))
+}
+
+/// Timestamp at which the vested amount grows next, `None` once nothing is left to unlock.
+/// Linear releases grow every second, so their next unlock is the next second after the cliff.
+pub fn next_unlock_at(settings: &PoolSettings, now: u64) -> Option<u64> {
+  if settings.sale_end >= settings.vest_end || now >= settings.vest_end {
+    return None;
+  }
+
+  let elapsed = now.checked_sub(settings.vest_cliff);
+  let next = match &settings.vesting_schedule.release {
+    VestingRelease::Linear => settings.vest_cliff.max(now).saturating_add(1),
+    VestingRelease::Periodic { period } => {
+      let next_period = elapsed.map_or(1, |elapsed| (elapsed / period).saturating_add(1));
+      settings.vest_cliff.saturating_add(next_period.saturating_mul(*period))
+    }
+    VestingRelease::Tranches { tranches } => {
+      let tranche = tranches
+        .iter()
+        .find(|tranche| match elapsed {
+          Some(elapsed) => tranche.offset > elapsed,
+          None => true,
+        })?;
+      settings.vest_cliff.saturating_add(tranche.offset)
+    }
+  };
+  // whatever the release leaves locked unlocks at vest end
+  Some(next.min(settings.vest_end))
+}
+
+/// `amount * numerator / denominator` rounded down, computed in u128 and capped at `amount`.
+fn portion(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+  if denominator == 0 {
+    return err!(ErrorCode::DivisionByZero);
+  }
+  let result = mul_div(amount as u128, numerator.min(denominator) as u128, denominator as u128, Rounding::Down)
+    .ok_or(error!(ErrorCode::Overflow))?;
+  u64::try_from(result).map_err(|_| error!(ErrorCode::Overflow))
+}
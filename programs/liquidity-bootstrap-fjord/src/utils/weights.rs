@@ -1,55 +1,109 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
 use crate::state::*;
+use super::fixed_point::*;
+use super::safe_math::*;
+use crate::errors::ErrorCode;
 
 pub const SOL: f64 = 1_000_000_000.0;
 
-#[error_code]
-pub enum ErrorCode {
-  #[msg("Amount out too large")]
-  AmountOutTooLarge,
-  #[msg("Amount in too large")]
-  AmountInTooLarge,
-  #[msg("Rust Pow needs u32")]
-  NotSafeForPow,
-}
+/// Fixed point precision of pool weights and percentages.
+pub const WEIGHT_PRECISION: u64 = 1_000_000_000;
+
+/// Fixed point precision of `max_share_price`, the price is whole assets per whole share.
+pub const PRICE_PRECISION: u64 = 1_000_000_000;
 
-pub fn linear_interpolation(x: u64, y: u64, i: u64, n: u64) -> u64 {
+/// Value `i / n` of the way from `x` to `y`, `i` past `n` stays at `y`.
+pub fn linear_interpolation(x: u64, y: u64, i: u64, n: u64) -> Result<u64> {
+  if n == 0 {
+    return err!(ErrorCode::DivisionByZero);
+  }
+  // the delta never exceeds |x - y| so it always fits back into a u64
+  let delta = (x.abs_diff(y) as u128 * i.min(n) as u128 / n as u128) as u64;
   if x > y {
-    return x - (((x - y) * i) / n);
+    x.safe_sub(delta)
   } else {
-    return x + (((y - x) * i) / n);
+    x.safe_add(delta)
+  }
+}
+
+/// Asset weight `seconds_elapsed` into the sale, following the pool's weight curve.
+pub fn compute_asset_weight(settings: &PoolSettings, seconds_elapsed: u64, total_seconds: u64) -> Result<u64> {
+  let weight_start = settings.weight_start;
+  let weight_end = settings.weight_end;
+  match &settings.weight_curve {
+    WeightCurve::Linear => linear_interpolation(weight_start, weight_end, seconds_elapsed, total_seconds),
+    WeightCurve::ExponentialDecay { decay_rate } => {
+      let elapsed = seconds_elapsed.min(total_seconds);
+      let progress = exponential_decay_progress(*decay_rate, elapsed, total_seconds).ok_or(error!(ErrorCode::Overflow))?;
+      linear_interpolation(weight_start, weight_end, progress, WEIGHT_PRECISION)
+    }
+    WeightCurve::Step { steps } => {
+      let last_step = (*steps as u64).safe_sub(1)?;
+      let step = seconds_elapsed.min(total_seconds).safe_mul(*steps as u64)?.safe_div(total_seconds)?.min(last_step);
+      linear_interpolation(weight_start, weight_end, step, last_step)
+    }
+    WeightCurve::PiecewiseLinear { breakpoints } => {
+      let elapsed = seconds_elapsed.min(total_seconds);
+      let end = WeightBreakpoint { offset: total_seconds, weight: weight_end };
+      let mut from = WeightBreakpoint { offset: 0, weight: weight_start };
+      for to in breakpoints.iter().copied().chain(std::iter::once(end)) {
+        if elapsed <= to.offset {
+          return linear_interpolation(from.weight, to.weight, elapsed.safe_sub(from.offset)?, to.offset.safe_sub(from.offset)?);
+        }
+        from = to;
+      }
+      Ok(weight_end)
+    }
   }
 }
 
+/// Share of the total weight change done after `elapsed` seconds, with 1e9 precision.
+///
+/// Follows `(1 - e^(-rate * t)) / (1 - e^(-rate))` where `t` is the elapsed fraction of
+/// the sale, so the curve starts at 0 and reaches exactly 1 at sale end.
+fn exponential_decay_progress(decay_rate: u64, elapsed: u64, total_seconds: u64) -> Option<u64> {
+  let rate = decay_rate as u128 * (WAD / WEIGHT_PRECISION as u128);
+  let x = mul_div(rate, elapsed as u128, total_seconds as u128, Rounding::Down)?;
+  let decayed = mul_div(WAD, WAD, exp_wad(x, Rounding::Up)?, Rounding::Down)?;
+  let decayed_total = mul_div(WAD, WAD, exp_wad(rate, Rounding::Up)?, Rounding::Down)?;
+  let progress = mul_div(
+    WAD.checked_sub(decayed)?,
+    WEIGHT_PRECISION as u128,
+    WAD.checked_sub(decayed_total)?,
+    Rounding::Down,
+  )?;
+  Some(progress.min(WEIGHT_PRECISION as u128) as u64)
+}
+
 pub fn compute_reserves_and_weights(
   pool: &Pool,
-  assets: u64, // These are calculated as asset().balanceOf(address(this)).rawSub(totalSwapFeesAsset), TODO
-  shares: u64,
-) -> (u64, u64, u64, u64) {
+  assets: u64, // asset vault balance
+  shares: u64, // share vault balance
+) -> Result<(u64, u64, u64, u64)> {
 
-  let asset_reserve: u64 = assets + pool.settings.virtual_assets;
-  let share_reserve: u64 = shares + pool.settings.virtual_shares - pool.total_purchased;
+  // Swap and referrer fees sit in the vaults until the pool closes, they are not liquidity
+  let asset_reserve: u64 = reserve(assets, pool.settings.virtual_assets, pool.total_swap_fees_asset.safe_add(pool.total_referred)?)?;
+  let share_reserve: u64 = reserve(shares, pool.settings.virtual_shares, pool.total_purchased.safe_add(pool.total_swap_fees_share)?)?;
 
-  let total_seconds: u64 = pool.settings.sale_end - pool.settings.sale_start;
+  let total_seconds: u64 = pool.settings.sale_end.safe_sub(pool.settings.sale_start)?;
 
   let mut seconds_elapsed: u64 = 0;
-  let unix_timestamp = match Clock::get() {
-    Ok(clock) => clock.unix_timestamp,
-    Err(_) => return (0, 0, 0, 0),
-  };
+  let unix_timestamp = Clock::get()?.unix_timestamp;
 
   if unix_timestamp as i128 > pool.settings.sale_start as i128 {
     seconds_elapsed = (unix_timestamp as i128 - pool.settings.sale_start as i128) as u64;
   }
-  let asset_weight: u64 = linear_interpolation(
-    pool.settings.weight_start,
-    pool.settings.weight_end,
-    seconds_elapsed,
-    total_seconds
-  );
-  let share_weight: u64 = 1_000_000_000 - asset_weight;
-  return (asset_reserve, share_reserve, asset_weight, share_weight);
+  let asset_weight: u64 = compute_asset_weight(&pool.settings, seconds_elapsed, total_seconds)?;
+  let share_weight: u64 = WEIGHT_PRECISION.safe_sub(asset_weight)?;
+  Ok((asset_reserve, share_reserve, asset_weight, share_weight))
+}
+
+/// Vault `balance` plus the virtual liquidity, minus the `locked` part that is owed to someone else.
+fn reserve(balance: u64, virtual_amount: u64, locked: u64) -> Result<u64> {
+  balance
+    .safe_add(virtual_amount)?
+    .checked_sub(locked)
+    .ok_or(error!(ErrorCode::InsufficientReserve))
 }
 
 pub fn scaled_reserves(
@@ -58,154 +112,206 @@ pub fn scaled_reserves(
   share_reserve: u64,
   asset_decimals: u8,
   share_decimals: u8,
-) -> (u64, u64) {
+) -> Result<(u64, u64)> {
   let asset = pool.settings.asset;
   let share = pool.settings.share;
-  let scaled_asset_token = scale_token_before(asset, asset_reserve, asset_decimals);
-  let scaled_share_token = scale_token_before(share, share_reserve, share_decimals);
-  (scaled_asset_token, scaled_share_token)
+  let scaled_asset_token = scale_token_before(asset, asset_reserve, asset_decimals, Rounding::Down)?;
+  let scaled_share_token = scale_token_before(share, share_reserve, share_decimals, Rounding::Down)?;
+  Ok((scaled_asset_token, scaled_share_token))
 }
 
 pub fn scale_token_before(
   token: Pubkey, // this is the token mint
   amount: u64,
-  decimals: u8
-) -> u64 {
+  decimals: u8,
+  rounding: Rounding,
+) -> Result<u64> {
   let base_decimals: u8 = 9;
   let mut scaled_amount: u64 = amount;
   if decimals < base_decimals {
     let dec_diff: u8 = base_decimals - decimals;
-    scaled_amount = amount * 10_u64.pow(dec_diff as u32);
+    scaled_amount = amount.safe_mul(pow10(dec_diff)?)?;
   } else if decimals > base_decimals {
     let dec_diff: u8 = decimals - base_decimals;
-    scaled_amount = amount / 10_u64.pow(dec_diff as u32);
+    scaled_amount = div_rounding_u64(amount, pow10(dec_diff)?, rounding)?;
   }
-  scaled_amount
+  Ok(scaled_amount)
 }
 
 pub fn scale_token_after(
   token: Pubkey,
   amount: u64,
-  decimals: u8
-) -> u64 {
+  decimals: u8,
+  rounding: Rounding,
+) -> Result<u64> {
   let base_decimals: u8 = 9;
   let mut scaled_amount: u64 = amount;
   if decimals < base_decimals {
     let dec_diff: u8 = base_decimals - decimals;
-    scaled_amount = amount / 10_u64.pow(dec_diff as u32);
+    scaled_amount = div_rounding_u64(amount, pow10(dec_diff)?, rounding)?;
   } else if decimals > base_decimals {
     let dec_diff: u8 = decimals - base_decimals;
-    scaled_amount = amount * 10_u64.pow(dec_diff as u32);
+    scaled_amount = amount.safe_mul(pow10(dec_diff)?)?;
   }
-  scaled_amount
+  Ok(scaled_amount)
 }
 
-pub fn get_amount_in(amount_out: f64, reserve_in: f64, reserve_out: f64, weight_in: f64, weight_out: f64) -> Result<u64> {
-  let MAX_PERCENTAGE_OUT = 0.3;
-  if amount_out > (reserve_out  * MAX_PERCENTAGE_OUT) {
+fn pow10(exponent: u8) -> Result<u64> {
+  10_u64.checked_pow(exponent as u32).ok_or(error!(ErrorCode::Overflow))
+}
+
+fn div_rounding_u64(amount: u64, divisor: u64, rounding: Rounding) -> Result<u64> {
+  match rounding {
+    Rounding::Up if divisor != 0 => Ok(amount.div_ceil(divisor)),
+    _ => amount.safe_div(divisor),
+  }
+}
+
+/// Assets (or shares) that must be paid in to take `amount_out` out of the pool:
+/// `reserve_in * ((reserve_out / (reserve_out - amount_out))^(weight_out / weight_in) - 1)`.
+/// Every step rounds up so the result is never below the exact value.
+/// Fails when `amount_out` is above `max_percentage_out` (1e9 precision) of `reserve_out`.
+pub fn get_amount_in(amount_out: u64, reserve_in: u64, reserve_out: u64, weight_in: u64, weight_out: u64, max_percentage_out: u64) -> Result<u64> {
+  // a drained output reserve can't be divided by, report it instead of an overflow
+  if reserve_out == 0 {
+    return err!(ErrorCode::DivisionByZero);
+  }
+  if amount_out as u128 * WEIGHT_PRECISION as u128 > reserve_out as u128 * max_percentage_out as u128 {
     return err!(ErrorCode::AmountOutTooLarge);
   }
-  let div_result = weight_out / weight_in;
-  let res: f64 = reserve_in * ((reserve_out / (reserve_out - amount_out)).powf(div_result) - 1.0);
-  Ok(res as u64)
+  let base = div_wad(reserve_out as u128, reserve_out.safe_sub(amount_out)? as u128, Rounding::Up)
+    .ok_or(ErrorCode::Overflow)?;
+  let exponent = div_wad(weight_out as u128, weight_in as u128, Rounding::Up)
+    .ok_or(ErrorCode::Overflow)?;
+  let power = pow_wad(base, exponent, Rounding::Up).ok_or(ErrorCode::Overflow)?;
+  let amount_in = mul_wad(reserve_in as u128, power.saturating_sub(WAD), Rounding::Up).ok_or(ErrorCode::Overflow)?;
+  u64::try_from(amount_in).map_err(|_| error!(ErrorCode::Overflow))
 }
 
-pub fn get_amount_out(amount_in: f64, reserve_in: f64, reserve_out: f64, weight_in: f64, weight_out: f64) -> Result<u64> {
-  let MAX_PERCENTAGE_IN = 0.3;
-  if amount_in > (reserve_in * MAX_PERCENTAGE_IN) {
+/// Assets (or shares) paid out for putting `amount_in` into the pool:
+/// `reserve_out * (1 - (reserve_in / (reserve_in + amount_in))^(weight_in / weight_out))`.
+/// Every step rounds so the result is never above the exact value.
+/// Fails when `amount_in` is above `max_percentage_in` (1e9 precision) of `reserve_in`.
+pub fn get_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, weight_in: u64, weight_out: u64, max_percentage_in: u64) -> Result<u64> {
+  // a drained input reserve can't be divided by, report it instead of an overflow
+  if reserve_in == 0 {
+    return err!(ErrorCode::DivisionByZero);
+  }
+  if amount_in as u128 * WEIGHT_PRECISION as u128 > reserve_in as u128 * max_percentage_in as u128 {
     return err!(ErrorCode::AmountInTooLarge);
   }
-  let div_result = weight_in / weight_out;
-  let res: f64 = reserve_out * (1.0 - (reserve_in / (reserve_in + amount_in)).powf(div_result));
-  Ok(res as u64)
+  // (reserve_in + amount_in) / reserve_in >= 1, so its inverse power is the ratio above
+  let base = div_wad(reserve_in as u128 + amount_in as u128, reserve_in as u128, Rounding::Down)
+    .ok_or(ErrorCode::Overflow)?;
+  let exponent = div_wad(weight_in as u128, weight_out as u128, Rounding::Down)
+    .ok_or(ErrorCode::Overflow)?;
+  let inverse_power = inv_pow_wad(base, exponent, Rounding::Up).ok_or(ErrorCode::Overflow)?;
+  let amount_out = mul_wad(reserve_out as u128, WAD.saturating_sub(inverse_power), Rounding::Down)
+    .ok_or(ErrorCode::Overflow)?;
+  u64::try_from(amount_out).map_err(|_| error!(ErrorCode::Overflow))
 }
 
-pub fn preview_assets_in(pool: &Pool, shares_out: u64, assets:u64, shares: u64, assets_decimals: u8, shares_decimals: u8) -> Result<u64> {
-  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(&pool, assets, shares);
-  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals);
-  let shares_out_scaled = scale_token_before(pool.settings.share, shares_out, shares_decimals);
-  let assets_in_result = get_amount_in(
-    shares_out_scaled as f64, 
-    asset_reserve_scaled as f64, 
-    share_reserve_scaled as f64, 
-    asset_weight as f64, 
-    share_weight as f64
-  );
-  if assets_in_result.is_err() {
-    return Err(assets_in_result.unwrap_err());
-  }
-  let mut assets_in = assets_in_result.unwrap();
-  let max_share_price = pool.settings.max_share_price;
-  if assets_in / shares_out_scaled > max_share_price {
-    assets_in = shares_out_scaled / max_share_price;
+/// Scaled assets worth `shares` scaled shares at the pool's max share price.
+pub fn assets_at_max_price(pool: &Pool, shares: u64, rounding: Rounding) -> Result<u128> {
+  mul_div(shares as u128, pool.settings.max_share_price as u128, PRICE_PRECISION as u128, rounding)
+    .ok_or(error!(ErrorCode::Overflow))
+}
+
+/// Scaled shares worth `assets` scaled assets at the pool's max share price.
+pub fn shares_at_max_price(pool: &Pool, assets: u64, rounding: Rounding) -> Result<u128> {
+  if pool.settings.max_share_price == 0 {
+    return err!(ErrorCode::DivisionByZero);
   }
-  assets_in = scale_token_after(pool.settings.asset, assets_in, assets_decimals);
+  mul_div(assets as u128, PRICE_PRECISION as u128, pool.settings.max_share_price as u128, rounding)
+    .ok_or(error!(ErrorCode::Overflow))
+}
+
+fn to_u64(amount: u128) -> Result<u64> {
+  u64::try_from(amount).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+pub fn preview_assets_in(pool: &Pool, shares_out: u64, assets:u64, shares: u64, assets_decimals: u8, shares_decimals: u8) -> Result<u64> {
+  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(pool, assets, shares)?;
+  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals)?;
+  let shares_out_scaled = scale_token_before(pool.settings.share, shares_out, shares_decimals, Rounding::Up)?;
+  let mut assets_in = get_amount_in(
+    shares_out_scaled,
+    asset_reserve_scaled,
+    share_reserve_scaled,
+    asset_weight,
+    share_weight,
+    pool.settings.max_percentage_out,
+  )?;
+  // buyers never pay more than the max share price
+  assets_in = to_u64((assets_in as u128).min(assets_at_max_price(pool, shares_out_scaled, Rounding::Up)?))?;
+  assets_in = scale_token_after(pool.settings.asset, assets_in, assets_decimals, Rounding::Up)?;
   Ok(assets_in)
 }
 
 pub fn preview_shares_out(pool: &Pool, assets_in: u64, assets: u64, shares: u64, assets_decimals: u8, shares_decimals: u8) -> Result<u64> {
-  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(&pool, assets, shares);
-  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals);
-  let assets_in_scaled = scale_token_before(pool.settings.asset, assets_in, assets_decimals);
-  let shares_out_result = get_amount_out(
-    assets_in_scaled as f64, 
-    asset_reserve_scaled as f64, 
-    share_reserve_scaled as f64,
-     asset_weight as f64, 
-    share_weight as f64
-  );
-  if shares_out_result.is_err() {
-    return Err(shares_out_result.unwrap_err());
-  }
-  let mut shares_out = shares_out_result.unwrap();
-  if assets_in_scaled / shares_out > pool.settings.max_share_price {
-    shares_out = assets_in_scaled * pool.settings.max_share_price;
-  }
-  shares_out = scale_token_after(pool.settings.share, shares_out, shares_decimals);
+  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(pool, assets, shares)?;
+  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals)?;
+  let assets_in_scaled = scale_token_before(pool.settings.asset, assets_in, assets_decimals, Rounding::Down)?;
+  let mut shares_out = get_amount_out(
+    assets_in_scaled,
+    asset_reserve_scaled,
+    share_reserve_scaled,
+    asset_weight,
+    share_weight,
+    pool.settings.max_percentage_in,
+  )?;
+  shares_out = to_u64((shares_out as u128).max(shares_at_max_price(pool, assets_in_scaled, Rounding::Down)?))?;
+  shares_out = scale_token_after(pool.settings.share, shares_out, shares_decimals, Rounding::Down)?;
   Ok(shares_out)
 }
 
 
 pub fn preview_assets_out(pool: &Pool, shares_in: u64, assets: u64, shares: u64, assets_decimals: u8, shares_decimals: u8) -> Result<u64> {
-  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(&pool, assets, shares);
-  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals);
-  let shares_in_scaled = scale_token_before(pool.settings.share, shares_in, shares_decimals);
-  let assets_out_result = get_amount_out(
-    shares_in_scaled as f64, 
-    share_reserve_scaled as f64, 
-    asset_reserve_scaled as f64, 
-    share_weight as f64, 
-    asset_weight as f64
-  );
-  if assets_out_result.is_err() {
-    return Err(assets_out_result.unwrap_err());
-  }
-  let mut assets_out = assets_out_result.unwrap();
-  if assets_out / shares_in_scaled > pool.settings.max_share_price {
-    assets_out = shares_in_scaled * pool.settings.max_share_price;
-  }
-  assets_out = scale_token_after(pool.settings.asset, assets_out, assets_decimals);
+  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(pool, assets, shares)?;
+  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals)?;
+  let shares_in_scaled = scale_token_before(pool.settings.share, shares_in, shares_decimals, Rounding::Down)?;
+  let mut assets_out = get_amount_out(
+    shares_in_scaled,
+    share_reserve_scaled,
+    asset_reserve_scaled,
+    share_weight,
+    asset_weight,
+    pool.settings.max_percentage_in,
+  )?;
+  // sellers never get more than the max share price
+  assets_out = to_u64((assets_out as u128).min(assets_at_max_price(pool, shares_in_scaled, Rounding::Down)?))?;
+  assets_out = scale_token_after(pool.settings.asset, assets_out, assets_decimals, Rounding::Down)?;
   Ok(assets_out)
 }
 
 pub fn preview_shares_in(pool: &Pool, assets_out: u64, assets: u64, shares: u64, assets_decimals: u8, shares_decimals: u8) -> Result<u64> {
-  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(&pool, assets, shares);
-  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals);
-  let assets_out_scaled = scale_token_before(pool.settings.asset, assets_out, assets_decimals);
-  let shares_in_result = get_amount_in(
-    assets_out_scaled as f64, 
-    share_reserve_scaled as f64, 
-    asset_reserve_scaled as f64, 
-    share_weight as f64, 
-    asset_weight as f64
-  );
-  if shares_in_result.is_err() {
-    return Err(shares_in_result.unwrap_err());
-  }
-  let mut shares_in = shares_in_result.unwrap();
-  if assets_out_scaled / shares_in > pool.settings.max_share_price {
-    shares_in = assets_out_scaled / pool.settings.max_share_price;
-  }
-  shares_in = scale_token_after(pool.settings.share, shares_in, shares_decimals);
+  let (asset_reserve, share_reserve, asset_weight, share_weight) = compute_reserves_and_weights(pool, assets, shares)?;
+  let (asset_reserve_scaled, share_reserve_scaled) = scaled_reserves(pool, asset_reserve, share_reserve, assets_decimals, shares_decimals)?;
+  let assets_out_scaled = scale_token_before(pool.settings.asset, assets_out, assets_decimals, Rounding::Up)?;
+  let mut shares_in = get_amount_in(
+    assets_out_scaled,
+    share_reserve_scaled,
+    asset_reserve_scaled,
+    share_weight,
+    asset_weight,
+    pool.settings.max_percentage_out,
+  )?;
+  shares_in = to_u64((shares_in as u128).max(shares_at_max_price(pool, assets_out_scaled, Rounding::Up)?))?;
+  shares_in = scale_token_after(pool.settings.share, shares_in, shares_decimals, Rounding::Up)?;
   Ok(shares_in)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HALF: u64 = WEIGHT_PRECISION / 2;
+  const MAX_PERCENTAGE: u64 = WEIGHT_PRECISION * 3 / 10;
+
+  #[test]
+  fn drained_reserves_fail_with_division_by_zero() {
+    let division_by_zero: Error = ErrorCode::DivisionByZero.into();
+    assert_eq!(get_amount_out(0, 0, 1_000, HALF, HALF, MAX_PERCENTAGE).unwrap_err(), division_by_zero);
+    assert_eq!(get_amount_in(0, 1_000, 0, HALF, HALF, MAX_PERCENTAGE).unwrap_err(), division_by_zero);
+  }
+}
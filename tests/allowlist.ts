@@ -0,0 +1,216 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert, expect } from "chai";
+import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  SOL,
+  buildAllowlist,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+describe("Allowlisted Sales", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1800);
+  const decimals = 6; // mint decimals
+  const allocation = SOL.mul(new anchor.BN(2)); // shares the buyer may purchase
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let outsider;
+  let outsiderAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _outsider,
+      userAssetVault: _outsiderAssetVault,
+    } = await createUser(assetMint, shareMint);
+    outsider = _outsider;
+    outsiderAssetVault = _outsiderAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  // the buyer is listed next to two unrelated entries so proofs have more than one level
+  const createAllowlistedPool = async () => {
+    const { root, proofs } = buildAllowlist([
+      { recipient: anchor.web3.Keypair.generate().publicKey, maxAllocation: SOL },
+      { recipient: buyer.publicKey, maxAllocation: allocation },
+      { recipient: anchor.web3.Keypair.generate().publicKey, maxAllocation: SOL },
+    ]);
+
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.whitelistMerkleRoot = root;
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    return { buyerProof: proofs[1], ...created };
+  };
+
+  const buyExactShares = (user, userAssetVault, userStats, { pool, assetVault, shareVault }, sharesOut, allowlist) =>
+    program.methods.swapAssetsForExactShares(
+      user.publicKey,
+      sharesOut,
+      SOL.mul(new anchor.BN(10)),
+      null,
+      allowlist,
+    ).accounts({
+      depositor: user.publicKey,
+      pool: pool.publicKey,
+      poolAssetsAccount: assetVault.publicKey,
+      poolSharesAccount: shareVault.publicKey,
+      poolAssetsMint: assetMint,
+      poolSharesMint: shareMint,
+      depositorAssetsAccount: userAssetVault,
+      recipientUserStats: userStats,
+      lbpFactorySetting: lbpFactoryPda,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([user])
+    .rpc();
+
+  it("should reject buys without a valid proof", async () => {
+    const created = await createAllowlistedPool();
+    const { pool, assetVault, shareVault, buyerProof } = created;
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { userStats: outsiderStats } = await createUserStats(pool.publicKey, outsider);
+
+    const expectInvalidProof = async (fn: () => Promise<any>, message: string) => {
+      try {
+        await fn();
+        expect.fail(message);
+      } catch (error) {
+        expect(error.error.errorMessage).to.equal("Invalid allowlist proof");
+      }
+    };
+
+    await expectInvalidProof(
+      () => buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL, null),
+      "buy without proof should fail"
+    );
+
+    await expectInvalidProof(
+      () => buyExactShares(outsider, outsiderAssetVault, outsiderStats, created, SOL, buyerProof),
+      "outsider should not reuse the buyer proof"
+    );
+
+    await expectInvalidProof(
+      () => buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL, { ...buyerProof, maxAllocation: allocation.mul(new anchor.BN(2)) }),
+      "inflated allocation should fail"
+    );
+
+    await expectInvalidProof(
+      () => swapExactAssetsForShares(SOL, pool, outsider, shareVault.publicKey, assetVault.publicKey, outsiderAssetVault, lbpFactoryPda, outsiderStats, assetMint, shareMint),
+      "exact assets buy without proof should fail"
+    );
+  });
+
+  it("should let listed buyers purchase up to their allocation", async () => {
+    const created = await createAllowlistedPool();
+    const { pool, buyerProof } = created;
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    await buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL, buyerProof);
+    await buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL, buyerProof);
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eq(allocation), "purchased the full allocation");
+
+    try {
+      await buyExactShares(buyer, buyerAssetVault, buyerStats, created, new anchor.BN(1), buyerProof);
+      expect.fail("buy above the allocation should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Allocation exceeded");
+    }
+  });
+
+  it("should accept proofs on exact assets buys", async () => {
+    const { pool, assetVault, shareVault, buyerProof } = await createAllowlistedPool();
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint,
+      null,
+      buyerProof
+    );
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eq(sharesOut), "purchase recorded");
+  });
+});
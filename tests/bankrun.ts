@@ -0,0 +1,90 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BankrunProvider } from "anchor-bankrun";
+import { BanksClient, Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import { Logs, PublicKey, Transaction, VersionedTransaction } from "@solana/web3.js";
+
+/*
+ * Runs the program in an in-process bank instead of a local validator, so tests
+ * control the clock and never wait in real time.
+ */
+
+type LogsCallback = (logs: Logs, context: { slot: number }) => void;
+
+let context: ProgramTestContext;
+let latestBlockhash: string;
+
+const logsListeners = new Map<number, LogsCallback>();
+let nextListenerId = 0;
+
+export const startBankrun = async () => {
+  context = await startAnchor(".", [], []);
+  latestBlockhash = context.lastBlockhash;
+
+  const banksClient = context.banksClient;
+  forwardLogs(banksClient);
+
+  // anchor-bankrun signs with `context.lastBlockhash`, which never changes, hand it the blockhash
+  // of the current slot so the same transaction sent again after a warp isn't a duplicate
+  const providerContext = Object.create(context, {
+    banksClient: { value: banksClient },
+    lastBlockhash: { get: () => latestBlockhash },
+  }) as ProgramTestContext;
+  const provider = new BankrunProvider(providerContext);
+
+  // the bankrun connection has no log subscriptions, anchor needs them for `program.addEventListener`
+  const connection = provider.connection as any;
+  connection.onLogs = (_filter: PublicKey, callback: LogsCallback) => {
+    const id = nextListenerId++;
+    logsListeners.set(id, callback);
+    return id;
+  };
+  connection.removeOnLogsListener = async (id: number) => {
+    logsListeners.delete(id);
+  };
+  return provider;
+};
+
+// Hands the logs of every processed transaction to the log subscribers
+const forwardLogs = (banksClient: BanksClient) => {
+  const tryProcessTransaction = banksClient.tryProcessTransaction.bind(banksClient);
+  banksClient.tryProcessTransaction = async (tx: Transaction | VersionedTransaction) => {
+    const result = await tryProcessTransaction(tx);
+    const signature = anchor.utils.bytes.bs58.encode(
+      tx instanceof VersionedTransaction ? tx.signatures[0] : tx.signature
+    );
+    const logs = result.meta?.logMessages ?? [];
+    const slot = Number((await banksClient.getClock()).slot);
+    logsListeners.forEach((callback) => callback({ signature, err: result.result, logs }, { slot }));
+    return result;
+  };
+};
+
+/* Clock */
+
+export const getClockTimestamp = async () => {
+  const clock = await context.banksClient.getClock();
+  return new anchor.BN(clock.unixTimestamp.toString());
+};
+
+// Moves to the next slot at `timestamp`, every following transaction runs at exactly that time
+export const setClockTimestamp = async (timestamp: anchor.BN) => {
+  const clock = await context.banksClient.getClock();
+  const slot = clock.slot + BigInt(1);
+  context.warpToSlot(slot);
+  context.setClock(
+    new Clock(slot, clock.epochStartTimestamp, clock.epoch, clock.leaderScheduleEpoch, BigInt(timestamp.toString()))
+  );
+  [latestBlockhash] = await context.banksClient.getLatestBlockhash();
+};
+
+/* Accounts */
+
+export const addLamports = async (pubkey: PublicKey, lamports: number) => {
+  const account = await context.banksClient.getAccount(pubkey);
+  context.setAccount(pubkey, {
+    lamports: (account?.lamports ?? 0) + lamports,
+    data: account?.data ?? Buffer.alloc(0),
+    owner: account?.owner ?? anchor.web3.SystemProgram.programId,
+    executable: account?.executable ?? false,
+  });
+};
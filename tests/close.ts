@@ -0,0 +1,221 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  SALE_WINDOW,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createUser,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  warpTo,
+} from "./utils";
+
+describe("Close Authorization", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1100);
+  const decimals = 6; // mint decimals
+  const closeGracePeriod = SALE_WINDOW;
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let stranger;
+
+  let lbpFactoryPda;
+  let lbpFactorySettingsAuthority;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority, closeGracePeriod);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    stranger = anchor.web3.Keypair.generate();
+    await fund(stranger.publicKey);
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createEndingPool = async (extendSaleOnPause = false) => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.saleEnd = (await getNow()).add(SALE_WINDOW);
+    poolSettings.extendSaleOnPause = extendSaleOnPause;
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    return { poolSettings, ...created };
+  };
+
+  const close = async ({ pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority }, closer) => {
+    let closeEvent = null;
+    const id = program.addEventListener("ClosePool", (event, slot) => {
+      closeEvent = event;
+    });
+
+    try {
+      await closePool(
+        pool.publicKey,
+        assetVault.publicKey,
+        assetVaultAuthority,
+        shareVault.publicKey,
+        shareVaultAuthority,
+        depositorAssetVault,
+        depositorShareVault,
+        feeShareVault,
+        feeAssetVault,
+        lbpFactoryPda,
+        closer
+      );
+    } finally {
+      program.removeEventListener(id);
+    }
+    return closeEvent;
+  };
+
+  it("should let the factory authority close right after sale end", async () => {
+    const created = await createEndingPool();
+    await warpTo(created.poolSettings.saleEnd);
+
+    const closeEvent = await close(created, lbpFactorySettingsAuthority);
+
+    assert.ok((await program.account.pool.fetch(created.pool.publicKey)).closed, "pool closed");
+    assert.ok(closeEvent && closeEvent.closer.equals(lbpFactorySettingsAuthority.publicKey), "closer recorded");
+  });
+
+  it("should only let anyone close after the grace period", async () => {
+    const created = await createEndingPool();
+    await warpTo(created.poolSettings.saleEnd);
+
+    try {
+      await close(created, stranger);
+      expect.fail("close during the grace period should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+
+    await warpTo(created.poolSettings.saleEnd.add(closeGracePeriod));
+    const closeEvent = await close(created, stranger);
+
+    assert.ok((await program.account.pool.fetch(created.pool.publicKey)).closed, "pool closed");
+    assert.ok(closeEvent && closeEvent.closer.equals(stranger.publicKey), "closer recorded");
+  });
+
+  const pause = async (pool) => {
+    await program.methods.pausePool(null)
+      .accounts({ pool: pool.publicKey, authority: depositor.publicKey })
+      .signers([depositor])
+      .rpc();
+  };
+
+  it("should keep the owner from closing while a pause is extending the sale", async () => {
+    const created = await createEndingPool(true);
+    const { pool, poolSettings } = created;
+
+    // paused with the whole sale window left
+    await pause(pool);
+    await warpTo(poolSettings.saleEnd);
+
+    try {
+      await close(created, depositor);
+      expect.fail("owner close during an extending pause should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Closing disallowed");
+    }
+
+    await program.methods.unpausePool(null)
+      .accounts({ pool: pool.publicKey, authority: depositor.publicKey })
+      .signers([depositor])
+      .rpc();
+    const { settings } = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(settings.saleEnd.eq(poolSettings.saleEnd.add(SALE_WINDOW)), "sale time given back");
+
+    await warpTo(settings.saleEnd);
+    await close(created, depositor);
+    assert.ok((await program.account.pool.fetch(pool.publicKey)).closed, "pool closed");
+  });
+
+  it("should let the factory authority close a pool left paused after sale end", async () => {
+    const created = await createEndingPool(true);
+    const { pool, poolSettings } = created;
+
+    await pause(pool);
+    await warpTo(poolSettings.saleEnd);
+
+    await close(created, lbpFactorySettingsAuthority);
+    assert.ok((await program.account.pool.fetch(pool.publicKey)).closed, "pool closed");
+  });
+
+  it("should let anyone close a pool left paused once the grace period passes", async () => {
+    const created = await createEndingPool(true);
+    const { pool, poolSettings } = created;
+
+    await pause(pool);
+    await warpTo(poolSettings.saleEnd);
+
+    try {
+      await close(created, stranger);
+      expect.fail("close during the grace period should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+
+    await warpTo(poolSettings.saleEnd.add(closeGracePeriod));
+    const closeEvent = await close(created, stranger);
+
+    assert.ok((await program.account.pool.fetch(pool.publicKey)).closed, "pool closed");
+    assert.ok(closeEvent && closeEvent.closer.equals(stranger.publicKey), "closer recorded");
+  });
+});
@@ -5,7 +5,7 @@ import { describe } from "mocha";
 import { LiquidityBootstrapFjord } from "../target/types/liquidity_bootstrap_fjord";
 import { Program } from "@coral-xyz/anchor";
 import NodeWallet from "@coral-xyz/anchor/dist/cjs/nodewallet";
-import { SOL, closePool, createMintAndVault, createUser, createUserStats, createVault } from "./utils";
+import { SOL, closePool, createMintAndVault, createUser, createUserStats, createVault, getPoolPda } from "./utils";
 import { program } from "@coral-xyz/anchor/dist/cjs/native/system";
 
 interface PoolSettings {
@@ -17,6 +17,8 @@ interface PoolSettings {
   maxSharePrice: anchor.BN;
   maxSharesOut: anchor.BN;
   maxAssetsIn: anchor.BN;
+  maxPercentageIn: anchor.BN;
+  maxPercentageOut: anchor.BN;
   weightStart: anchor.BN;
   weightEnd: anchor.BN;
   saleStart: anchor.BN;
@@ -24,6 +26,13 @@ interface PoolSettings {
   vestCliff: anchor.BN;
   vestEnd: anchor.BN;
   sellingAllowed: boolean;
+  extendSaleOnPause: boolean;
+  whitelistMerkleRoot: number[] | null;
+  maxSharesPerUser: anchor.BN | null;
+  maxAssetsPerUser: anchor.BN | null;
+  minAssetsPerBuy: anchor.BN | null;
+  weightCurve: any;
+  vestingSchedule: any;
 }
 
 const createLBPManager = async ({
@@ -57,7 +66,8 @@ const createLBPManager = async ({
       feeReciever,
       platformFee,
       referrerFee,
-      swapFee
+      swapFee,
+      new anchor.BN(86400)
     )
     .accounts({
       authority: feeReciever,
@@ -233,38 +243,32 @@ const createPool = async (
   initialAssetAmount: anchor.BN,
 ) => {
 
-  const pool = anchor.web3.Keypair.generate();
-  const assetVault = anchor.web3.Keypair.generate();
-  const shareVault = anchor.web3.Keypair.generate();
-
-  const [assetVaultAuthority, assetVaultNonce] =
+  // pools live at a PDA derived from the factory and the pool id
+  const pool = { publicKey: getPoolPda(lbpFactoryPda, poolId) };
+  const [assetVaultAuthority] =
       anchor.web3.PublicKey.findProgramAddressSync(
           [anchor.utils.bytes.utf8.encode("asset"), pool.publicKey.toBuffer()],
           program.programId
       );
-  const [shareVaultAuthority, shareVaultNonce] =
+  const [shareVaultAuthority] =
       anchor.web3.PublicKey.findProgramAddressSync(
           [anchor.utils.bytes.utf8.encode("share"), pool.publicKey.toBuffer()],
           program.programId
   );
 
-  const tx = new anchor.web3.Transaction();
-  tx.add(
-      await program.account.pool.createInstruction(pool),
-      ...(await createTokenAccountInstrs(
-          provider,
-          assetVault.publicKey,
-          assetMint,
-          assetVaultAuthority
-      )),
-      ...(await createTokenAccountInstrs(
-          provider,
-          shareVault.publicKey,
-          shareMint,
-          shareVaultAuthority
-      )),
-  )
-  await provider.sendAndConfirm(tx, [pool, assetVault, shareVault]);
+  // vaults are created by the program at PDAs owned by the vault authorities
+  const [assetVaultPda] =
+      anchor.web3.PublicKey.findProgramAddressSync(
+          [anchor.utils.bytes.utf8.encode("asset_vault"), pool.publicKey.toBuffer()],
+          program.programId
+      );
+  const [shareVaultPda] =
+      anchor.web3.PublicKey.findProgramAddressSync(
+          [anchor.utils.bytes.utf8.encode("share_vault"), pool.publicKey.toBuffer()],
+          program.programId
+  );
+  const assetVault = { publicKey: assetVaultPda };
+  const shareVault = { publicKey: shareVaultPda };
 
   await program.methods
       .createPool(
@@ -272,18 +276,21 @@ const createPool = async (
           poolId, 
           initialShareAmount, 
           initialAssetAmount,
-          shareVaultNonce,
-          assetVaultNonce,
       )
       .accounts({
           pool: pool.publicKey,
+          assetMint: assetMint,
+          shareMint: shareMint,
+          assetVaultAuthority: assetVaultAuthority,
+          shareVaultAuthority: shareVaultAuthority,
           assetVault: assetVault.publicKey,
           shareVault: shareVault.publicKey,
           depositorAssetVault: depositorAssetVault,
           depositorShareVault: depositorShareVault,
           depositor: depositor.publicKey,
           lbpFactorySetting:lbpFactoryPda,
-          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          assetTokenProgram: splToken.TOKEN_PROGRAM_ID,
+          shareTokenProgram: splToken.TOKEN_PROGRAM_ID,
           rent: SYSVAR_RENT_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -346,6 +353,8 @@ const swapAssetsForExactShares = async ({
     depositor.publicKey,
     sharesOut,
     maxAssetsIn,
+    null,
+    null,
   ).accounts({
     depositor: depositor.publicKey,
     pool: pool,
@@ -362,50 +371,64 @@ const swapAssetsForExactShares = async ({
   .rpc();
 };
 
-describe("lbp-examples", async () => {
-  // Code to get Provider
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
-
-  // Program instance
-  const program = anchor.workspace
-    .LiquidityBootstrapFjord as Program<LiquidityBootstrapFjord>;
-
-  // You can use this on a forntend to get the program:
-  // IDL is whatever is in the target/types/liquidity_bootstrap_fjord.ts
-  // const program = new Program<LiquidityBootstrapFjord>(IDL, programId, {
-  //   connection,
-  // });
-
-  // This is just a sample token
-  const defaultInitialAssetAmount = SOL.mul(new anchor.BN(1000));
-  const defaultInitialShareAmount = SOL.mul(new anchor.BN(1000));
-  const [assetMint, assetGod] = await createMintAndVault(
-    defaultInitialAssetAmount,
-    provider.wallet.publicKey,
-    6
-  );
-  const [shareMint, shareGod] = await createMintAndVault(
-    defaultInitialShareAmount,
-    provider.wallet.publicKey,
-    6
-  );
+describe("lbp-examples", () => {
+  let provider: anchor.AnchorProvider;
+  let program: Program<LiquidityBootstrapFjord>;
 
-  // This is the token account for the depositor for the token
-  const { 
-    user: _depositor, 
-    userAssetVault: _depositorAssetVault, 
-    userShareVault: _depositorShareVault 
-  } = await createUser(assetMint, shareMint);
-  let depositor = _depositor;
-  let depositorAssetVault = _depositorAssetVault;
-  let depositorShareVault = _depositorShareVault;
+  let assetMint;
+  let shareMint;
 
-  const fee_recipient = provider.wallet.publicKey;
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let fee_recipient;
 
   const poolAssetKp = anchor.web3.Keypair.generate();
   const poolShareKp = anchor.web3.Keypair.generate();
 
+  before(async () => {
+    // Code to get Provider, the tests run against the bankrun provider set up in utils,
+    // a client would use anchor.AnchorProvider.env() or its wallet adapter instead
+    provider = anchor.getProvider() as anchor.AnchorProvider;
+
+    // Program instance
+    program = anchor.workspace
+      .LiquidityBootstrapFjord as Program<LiquidityBootstrapFjord>;
+
+    // You can use this on a forntend to get the program:
+    // IDL is whatever is in the target/types/liquidity_bootstrap_fjord.ts
+    // const program = new Program<LiquidityBootstrapFjord>(IDL, programId, {
+    //   connection,
+    // });
+
+    // This is just a sample token
+    const defaultInitialAssetAmount = SOL.mul(new anchor.BN(1000));
+    const defaultInitialShareAmount = SOL.mul(new anchor.BN(1000));
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      6
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      6
+    );
+
+    // This is the token account for the depositor for the token
+    const { 
+      user: _depositor, 
+      userAssetVault: _depositorAssetVault, 
+      userShareVault: _depositorShareVault 
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+
+    fee_recipient = provider.wallet.publicKey;
+  });
+
   it("Example", async () => {
     // Create an LBP Manager
     const lbpFactoryPda = await createLBPManager({
@@ -468,6 +491,8 @@ describe("lbp-examples", async () => {
       maxSharePrice: new anchor.BN(1000),
       maxSharesOut: new anchor.BN(1000),
       maxAssetsIn: new anchor.BN(1000),
+      maxPercentageIn: new anchor.BN(300_000_000),
+      maxPercentageOut: new anchor.BN(300_000_000),
       weightStart: new anchor.BN(1000),
       weightEnd: new anchor.BN(1000),
       saleStart: new anchor.BN(1000),
@@ -475,6 +500,13 @@ describe("lbp-examples", async () => {
       vestCliff: new anchor.BN(1000),
       vestEnd: new anchor.BN(1000),
       sellingAllowed: true,
+      extendSaleOnPause: false,
+      whitelistMerkleRoot: null,
+      maxSharesPerUser: null,
+      maxAssetsPerUser: null,
+      minAssetsPerBuy: null,
+      weightCurve: { linear: {} },
+      vestingSchedule: { initialUnlockBps: 0, release: { linear: {} } },
     };
 
     const initialAssetAmount = new anchor.BN(1000);
@@ -553,7 +585,8 @@ describe("lbp-examples", async () => {
       managerShareVault,
       feeShareVault,
       feeAssetVault,
-      lbpFactoryPda
+      lbpFactoryPda,
+      depositor
     );
   });
 });
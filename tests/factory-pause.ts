@@ -0,0 +1,270 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert, expect } from "chai";
+import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  SALE_WINDOW,
+  SOL,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+  warpTo,
+} from "./utils";
+
+describe("Factory Pause", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1600);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+  let buyerShareVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let lbpFactoryPda;
+  let lbpFactorySettingsAuthority;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+      userShareVault: _buyerShareVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+    buyerShareVault = _buyerShareVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  afterEach(async () => {
+    // leave the factory unpaused for the next test
+    const settings = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    if (settings.paused) {
+      await unpauseFactory(lbpFactorySettingsAuthority);
+    }
+  });
+
+  const pauseFactory = (signer) =>
+    program.methods.pauseFactory()
+      .accounts({ lbpFactorySetting: lbpFactoryPda, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const unpauseFactory = (signer) =>
+    program.methods.unpauseFactory()
+      .accounts({ lbpFactorySetting: lbpFactoryPda, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const buy = (pool, assetVault, shareVault, buyerStats) =>
+    swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+  const previewSharesOut = (pool, assetVault, shareVault) =>
+    program.methods.previewSharesOut(SOL)
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolSharesAccount: shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .view();
+
+  it("should only let the factory authority pause and unpause", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+    await fund(stranger.publicKey);
+
+    try {
+      await pauseFactory(stranger);
+      expect.fail("stranger should not pause the factory");
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    let pausedEvent = null;
+    const id = program.addEventListener("FactoryPaused", (event, slot) => {
+      pausedEvent = event;
+    });
+    try {
+      await pauseFactory(lbpFactorySettingsAuthority);
+    } finally {
+      program.removeEventListener(id);
+    }
+    assert.ok((await program.account.lbpFactorySetting.fetch(lbpFactoryPda)).paused, "factory paused");
+    assert.ok(pausedEvent && pausedEvent.lbpFactory.equals(lbpFactoryPda));
+
+    try {
+      await unpauseFactory(stranger);
+      expect.fail("stranger should not unpause the factory");
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    await unpauseFactory(lbpFactorySettingsAuthority);
+    assert.ok(!(await program.account.lbpFactorySetting.fetch(lbpFactoryPda)).paused, "factory unpaused");
+  });
+
+  it("should block swaps and previews while paused", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    await pauseFactory(lbpFactorySettingsAuthority);
+
+    try {
+      await buy(pool, assetVault, shareVault, buyerStats);
+      expect.fail("swap should fail while the factory is paused");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Factory is paused");
+    }
+
+    try {
+      await previewSharesOut(pool, assetVault, shareVault);
+      expect.fail("preview should fail while the factory is paused");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Factory is paused");
+    }
+
+    await unpauseFactory(lbpFactorySettingsAuthority);
+
+    // trading resumes once unpaused
+    assert.ok((await previewSharesOut(pool, assetVault, shareVault)).gtn(0));
+    const { sharesOut } = await buy(pool, assetVault, shareVault, buyerStats);
+    assert.ok(sharesOut.gtn(0));
+  });
+
+  it("should still let buyers redeem while paused", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleEnd = now.add(SALE_WINDOW);
+    poolSettings.vestCliff = poolSettings.saleEnd;
+    poolSettings.vestEnd = poolSettings.saleEnd.add(new anchor.BN(1));
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+      shareVaultAuthority,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await buy(pool, assetVault, shareVault, buyerStats);
+
+    await warpTo(poolSettings.vestEnd);
+    await closePool(
+      pool.publicKey,
+      assetVault.publicKey,
+      assetVaultAuthority,
+      shareVault.publicKey,
+      shareVaultAuthority,
+      depositorAssetVault,
+      depositorShareVault,
+      feeShareVault,
+      feeAssetVault,
+      lbpFactoryPda,
+      depositor
+    );
+
+    await pauseFactory(lbpFactorySettingsAuthority);
+
+    await program.methods
+      .redeem(null, buyerShareVault)
+      .accounts({
+        user: buyer.publicKey,
+        pool: pool.publicKey,
+        shareVault: shareVault.publicKey,
+        shareVaultAuthority: shareVaultAuthority,
+        lbpFactorySetting: lbpFactoryPda,
+        buyerStats: buyerStats,
+        recipientShareVault: buyerShareVault,
+        shareMint: shareMint,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.claimed.eq(sharesOut), "redeemed while paused");
+  });
+});
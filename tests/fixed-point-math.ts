@@ -0,0 +1,201 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { SOL, createMintAndVault, createPool, createUser, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getSwapFees, initialize, program, provider } from "./utils";
+
+/* High precision reference, every value is scaled by 1e40 */
+
+const PRECISION = new anchor.BN(10).pow(new anchor.BN(40));
+
+const twoAtanh = (m: anchor.BN) => {
+  const s = m.sub(PRECISION).mul(PRECISION).div(m.add(PRECISION));
+  const sSquared = s.mul(s).div(PRECISION);
+  let sum = s;
+  let term = s;
+  for (let n = 1; ; n++) {
+    term = term.mul(sSquared).div(PRECISION);
+    if (term.isZero()) break;
+    sum = sum.add(term.divn(2 * n + 1));
+  }
+  return sum.muln(2);
+};
+
+const LN2 = twoAtanh(PRECISION.muln(2));
+
+const refLn = (x: anchor.BN) => {
+  let k = 0;
+  let m = x;
+  while (m.gte(PRECISION.muln(2))) {
+    m = m.divn(2);
+    k++;
+  }
+  return LN2.muln(k).add(twoAtanh(m));
+};
+
+const refExp = (y: anchor.BN) => {
+  const k = y.div(LN2);
+  const r = y.sub(LN2.mul(k));
+  let sum = PRECISION.add(r);
+  let term = r;
+  for (let n = 2; ; n++) {
+    term = term.mul(r).div(PRECISION.muln(n));
+    if (term.isZero()) break;
+    sum = sum.add(term);
+  }
+  return sum.shln(k.toNumber());
+};
+
+// reserveIn * ((reserveOut / (reserveOut - amountOut))^(weightOut / weightIn) - 1), scaled
+const refAmountIn = (amountOut, reserveIn, reserveOut, weightIn, weightOut) => {
+  const ratio = reserveOut.mul(PRECISION).div(reserveOut.sub(amountOut));
+  const exponent = weightOut.mul(PRECISION).div(weightIn);
+  const power = refExp(refLn(ratio).mul(exponent).div(PRECISION));
+  return reserveIn.mul(power.sub(PRECISION));
+};
+
+// reserveOut * (1 - (reserveIn / (reserveIn + amountIn))^(weightIn / weightOut)), scaled
+const refAmountOut = (amountIn, reserveIn, reserveOut, weightIn, weightOut) => {
+  const ratio = reserveIn.add(amountIn).mul(PRECISION).div(reserveIn);
+  const exponent = weightIn.mul(PRECISION).div(weightOut);
+  const power = refExp(refLn(ratio).mul(exponent).div(PRECISION));
+  return reserveOut.mul(PRECISION.sub(PRECISION.mul(PRECISION).div(power)));
+};
+
+// deterministic samples so failures are reproducible
+const samples = (max: anchor.BN, count: number) => {
+  let seed = new anchor.BN(0x2545f491);
+  const modulus = new anchor.BN(2).pow(new anchor.BN(61)).subn(1);
+  const result = [];
+  for (let i = 0; i < count; i++) {
+    seed = seed.mul(new anchor.BN(48271)).mod(modulus);
+    result.push(seed.mod(max).addn(1));
+  }
+  return result;
+};
+
+describe("Fixed Point Math", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(800);
+  const decimals = 9; // mint decimals, matches the internal precision so no scaling happens
+  const sampleCount = 25;
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    const { user: feeRecipient } = await createUser(assetMint, shareMint);
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const cases = [
+    { name: "balanced weights", assetWeight: SOL.divn(2), maxAmount: SOL.muln(290) },
+    { name: "low asset weight", assetWeight: SOL.divn(10), maxAmount: SOL.muln(50) },
+    { name: "high asset weight", assetWeight: SOL.divn(10).muln(9), maxAmount: SOL.muln(50) },
+    { name: "min asset weight", assetWeight: SOL.divn(100), maxAmount: SOL.muln(5) },
+    { name: "max asset weight", assetWeight: SOL.divn(100).muln(99), maxAmount: SOL.muln(5) },
+  ];
+
+  for (const { name, assetWeight, maxAmount } of cases) {
+    it(`matches the reference and rounds in favor of the pool with ${name}`, async () => {
+      const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+      poolSettings.weightStart = assetWeight;
+      poolSettings.weightEnd = assetWeight;
+
+      const {
+        pool,
+        assetVault,
+        shareVault,
+      } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+      const accounts = {
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolSharesAccount: shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        lbpFactorySetting: lbpFactoryPda,
+      };
+
+      const output = await program.methods.computeReservesAndWeights()
+        .accounts({
+          pool: pool.publicKey,
+          poolAssetVault: assetVault.publicKey,
+          poolShareVault: shareVault.publicKey,
+        })
+        .view();
+      const { assetReserve, shareReserve, assetWeight: wAsset, shareWeight: wShare } = output;
+      const swapFee = await getSwapFees(lbpFactoryPda);
+
+      // the on-chain result may exceed the exact value by a few units plus the
+      // propagated fixed point error, which scales with the reserve
+      const tolerance = (reserve: anchor.BN) => PRECISION.mul(reserve.div(new anchor.BN(1_000_000_000_000)).addn(2));
+
+      for (const amount of samples(maxAmount, sampleCount)) {
+        // amount in directions must never be below the exact value
+        const assetsIn = await program.methods.previewAssetsIn(amount).accounts(accounts).view();
+        const refAssetsIn = refAmountIn(amount, assetReserve, shareReserve, wAsset, wShare);
+        assert.ok(assetsIn.mul(PRECISION).gte(refAssetsIn), `assetsIn ${assetsIn} below reference for ${amount}`);
+        assert.ok(assetsIn.mul(PRECISION).sub(refAssetsIn).lte(tolerance(assetReserve)), `assetsIn ${assetsIn} too far from reference for ${amount}`);
+
+        const sharesIn = await program.methods.previewSharesIn(amount).accounts(accounts).view();
+        const refSharesIn = refAmountIn(amount, shareReserve, assetReserve, wShare, wAsset);
+        assert.ok(sharesIn.mul(PRECISION).gte(refSharesIn), `sharesIn ${sharesIn} below reference for ${amount}`);
+        assert.ok(sharesIn.mul(PRECISION).sub(refSharesIn).lte(tolerance(shareReserve)), `sharesIn ${sharesIn} too far from reference for ${amount}`);
+
+        // amount out directions must never be above the exact value
+        const sharesOut = await program.methods.previewSharesOut(amount).accounts(accounts).view();
+        // buys are priced net of the swap fee
+        const refSharesOut = refAmountOut(amount.sub(amount.mul(swapFee).div(SOL)), assetReserve, shareReserve, wAsset, wShare);
+        assert.ok(sharesOut.mul(PRECISION).lte(refSharesOut), `sharesOut ${sharesOut} above reference for ${amount}`);
+        assert.ok(refSharesOut.sub(sharesOut.mul(PRECISION)).lte(tolerance(shareReserve)), `sharesOut ${sharesOut} too far from reference for ${amount}`);
+
+        const assetsOut = await program.methods.previewAssetsOut(amount).accounts(accounts).view();
+        const refAssetsOut = refAmountOut(amount, shareReserve, assetReserve, wShare, wAsset);
+        assert.ok(assetsOut.mul(PRECISION).lte(refAssetsOut), `assetsOut ${assetsOut} above reference for ${amount}`);
+        assert.ok(refAssetsOut.sub(assetsOut.mul(PRECISION)).lte(tolerance(assetReserve)), `assetsOut ${assetsOut} too far from reference for ${amount}`);
+      }
+    });
+  }
+});
@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import * as splToken from "@solana/spl-token";
 import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { BN_0, BN_1, ONE_DAY, SOL, TWO_DAYS, ZERO_ADDRESS, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { BN_0, BN_1, ONE_DAY, SOL, TWO_DAYS, ZERO_ADDRESS, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getPoolPda, initialize, program, provider, swapExactAssetsForShares } from "./utils";
 
 describe("Pool Creation Tests", () => {
   /* Settings */
@@ -243,5 +243,30 @@ describe("Pool Creation Tests", () => {
     assert.ok(poolAccount.assetVaultAuthority.toString() === assetVaultAuthority.toString(), "Asset vault authority address should be the same");
     assert.ok(poolAccount.shareVault.toString() === shareVault.publicKey.toString(), "Share vault address should be the same");
     assert.ok(poolAccount.shareVaultAuthority.toString() === shareVaultAuthority.toString(), "Share vault authority address should be the same");
+
+    // vaults are created by the program and owned by the canonical authorities
+    const assetVaultAccount = await splToken.getAccount(provider.connection, assetVault.publicKey);
+    assert.ok(assetVaultAccount.owner.equals(assetVaultAuthority), "Asset vault should be owned by the asset vault authority");
+    assert.ok(assetVaultAccount.mint.equals(assetMint), "Asset vault should hold the asset mint");
+    const shareVaultAccount = await splToken.getAccount(provider.connection, shareVault.publicKey);
+    assert.ok(shareVaultAccount.owner.equals(shareVaultAuthority), "Share vault should be owned by the share vault authority");
+    assert.ok(shareVaultAccount.mint.equals(shareMint), "Share vault should hold the share mint");
+  });
+
+  it("should create the pool at the canonical address and reject a duplicate id", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+
+    const { pool } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    assert.ok(pool.publicKey.equals(getPoolPda(lbpFactoryPda, poolId)), "pool address should be derived from factory and id");
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.id.eq(poolId), "pool id should be stored");
+
+    try {
+      await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.logs.join("\n")).to.contain("already in use");
+    }
   });
 });
@@ -0,0 +1,182 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  ONE_DAY,
+  SOL,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  expectError,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+const U64_MAX = new anchor.BN(2).pow(new anchor.BN(64)).subn(1);
+
+// failures the math helpers may report, anything else (e.g. a panic) is a bug
+const TYPED_ERRORS = [
+  "Arithmetic overflow",
+  "Arithmetic underflow",
+  "Division by zero",
+  "Insufficient reserve",
+  "Amount in too large",
+  "Amount out too large",
+];
+
+// deterministic samples spread over every magnitude of the u64 range so failures are reproducible
+const samples = (count: number) => {
+  let seed = new anchor.BN(0x2545f491);
+  const modulus = new anchor.BN(2).pow(new anchor.BN(61)).subn(1);
+  const next = () => {
+    seed = seed.mul(new anchor.BN(48271)).mod(modulus);
+    return seed;
+  };
+  const result = [];
+  for (let i = 0; i < count; i++) {
+    const bits = next().modn(64) + 1;
+    const max = new anchor.BN(2).pow(new anchor.BN(bits));
+    result.push(next().mul(next()).mod(max));
+  }
+  return result;
+};
+
+describe("Math Boundaries", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(2000);
+  const decimals = 6; // mint decimals, below the internal precision so amounts get scaled
+  const sampleCount = 20;
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const previewAccounts = ({ pool, assetVault, shareVault }) => ({
+    pool: pool.publicKey,
+    poolAssetsAccount: assetVault.publicKey,
+    poolSharesAccount: shareVault.publicKey,
+    poolAssetsMint: assetMint,
+    poolSharesMint: shareMint,
+    lbpFactorySetting: lbpFactoryPda,
+  });
+
+  it("should return a result or a typed error for any preview amount", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const accounts = previewAccounts(created);
+
+    const previews = {
+      previewAssetsIn: (amount) => program.methods.previewAssetsIn(amount),
+      previewSharesIn: (amount) => program.methods.previewSharesIn(amount),
+      previewSharesOut: (amount) => program.methods.previewSharesOut(amount),
+      previewAssetsOut: (amount) => program.methods.previewAssetsOut(amount),
+    };
+
+    const amounts = [new anchor.BN(0), new anchor.BN(1), SOL, U64_MAX.subn(1), U64_MAX, ...samples(sampleCount)];
+    for (const amount of amounts) {
+      for (const [name, preview] of Object.entries(previews)) {
+        try {
+          const result = await preview(amount).accounts(accounts).view();
+          assert.ok(result.lte(U64_MAX), `${name} result out of range for ${amount}`);
+        } catch (error) {
+          assert.ok(error.error, `${name} failed without a program error for ${amount}: ${error}`);
+          assert.include(TYPED_ERRORS, error.error.errorMessage, `${name} failed with an unexpected error for ${amount}`);
+        }
+      }
+    }
+  });
+
+  it("should reject a u64::MAX buy with a typed error", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    await expectError(
+      () => swapExactAssetsForShares(U64_MAX, pool, buyer, shareVault.publicKey, assetVault.publicKey, buyerAssetVault, lbpFactoryPda, buyerStats, assetMint, shareMint),
+      "Amount in too large",
+      "buy above the reserve should fail"
+    );
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eqn(0), "nothing purchased");
+  });
+
+  it("should reject an empty sale window", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.add(ONE_DAY);
+    poolSettings.saleEnd = poolSettings.saleStart;
+
+    await expectError(
+      () => createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint),
+      "Sale Period Low",
+      "creating a pool with an empty sale window should fail"
+    );
+  });
+});
@@ -0,0 +1,182 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  SOL,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  expectError,
+  fund,
+  getDefaultPoolSettings,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+describe("Max Swap Percentage", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(2200);
+  const decimals = 9; // mint decimals, matches the internal precision so no scaling happens
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createLimitedPool = async (limits) => {
+    const poolSettings = { ...(await getDefaultPoolSettings(assetMint, shareMint)), ...limits };
+    return await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+  };
+
+  const previewAccounts = ({ pool, assetVault, shareVault }) => ({
+    pool: pool.publicKey,
+    poolAssetsAccount: assetVault.publicKey,
+    poolSharesAccount: shareVault.publicKey,
+    poolAssetsMint: assetMint,
+    poolSharesMint: shareMint,
+    lbpFactorySetting: lbpFactoryPda,
+  });
+
+  // the pool holds 1000 assets and 1000 shares
+  const percentOfReserve = (percent: number) => SOL.muln(10 * percent);
+
+  it("should validate the percentages on pool creation", async () => {
+    await expectError(
+      () => createLimitedPool({ maxPercentageIn: new anchor.BN(0) }),
+      "Invalid Max Percentage",
+      "zero max percentage in should be rejected"
+    );
+
+    await expectError(
+      () => createLimitedPool({ maxPercentageOut: SOL }),
+      "Invalid Max Percentage",
+      "taking the whole reserve out should be rejected"
+    );
+
+    await expectError(
+      () => createLimitedPool({ maxPercentageIn: SOL.addn(1) }),
+      "Invalid Max Percentage",
+      "max percentage in above 100% should be rejected"
+    );
+  });
+
+  it("should report which side of a swap is too large", async () => {
+    const created = await createLimitedPool({});
+    const accounts = previewAccounts(created);
+
+    await expectError(
+      () => program.methods.previewSharesOut(percentOfReserve(31)).accounts(accounts).view(),
+      "Amount in too large",
+      "buying with more than 30% of the asset reserve should fail"
+    );
+    await expectError(
+      () => program.methods.previewAssetsIn(percentOfReserve(31)).accounts(accounts).view(),
+      "Amount out too large",
+      "buying more than 30% of the share reserve should fail"
+    );
+    await expectError(
+      () => program.methods.previewAssetsOut(percentOfReserve(31)).accounts(accounts).view(),
+      "Amount in too large",
+      "selling more than 30% of the share reserve should fail"
+    );
+    await expectError(
+      () => program.methods.previewSharesIn(percentOfReserve(31)).accounts(accounts).view(),
+      "Amount out too large",
+      "taking more than 30% of the asset reserve should fail"
+    );
+
+    // swaps report the same error
+    const { pool, assetVault, shareVault } = created;
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    await expectError(
+      () => swapExactAssetsForShares(percentOfReserve(31), pool, buyer, shareVault.publicKey, assetVault.publicKey, buyerAssetVault, lbpFactoryPda, buyerStats, assetMint, shareMint),
+      "Amount in too large",
+      "swap above the limit should fail"
+    );
+  });
+
+  it("should follow the configured percentages", async () => {
+    const created = await createLimitedPool({ maxPercentageIn: SOL.divn(2), maxPercentageOut: SOL.divn(10) });
+    const accounts = previewAccounts(created);
+
+    // half of the asset reserve may go in
+    const sharesOut = await program.methods.previewSharesOut(percentOfReserve(40)).accounts(accounts).view();
+    assert.ok(sharesOut.gtn(0), "buy within the raised limit");
+    await expectError(
+      () => program.methods.previewSharesOut(percentOfReserve(51)).accounts(accounts).view(),
+      "Amount in too large",
+      "buy above the raised limit should fail"
+    );
+
+    // a tenth of the share reserve may come out
+    const assetsIn = await program.methods.previewAssetsIn(percentOfReserve(10)).accounts(accounts).view();
+    assert.ok(assetsIn.gtn(0), "buy within the lowered limit");
+    await expectError(
+      () => program.methods.previewAssetsIn(percentOfReserve(11)).accounts(accounts).view(),
+      "Amount out too large",
+      "buy above the lowered limit should fail"
+    );
+  });
+});
@@ -0,0 +1,179 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  SOL,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getSwapFees,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+describe("Max Share Price", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(2100);
+  const decimals = 9; // mint decimals, matches the internal precision so no scaling happens
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  // equal reserves and weights, so the spot price is one asset per share
+  const createPricedPool = async (maxSharePrice: anchor.BN) => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.maxSharePrice = maxSharePrice;
+    return await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+  };
+
+  const previewAll = async ({ pool, assetVault, shareVault }, amount: anchor.BN) => {
+    const accounts = {
+      pool: pool.publicKey,
+      poolAssetsAccount: assetVault.publicKey,
+      poolSharesAccount: shareVault.publicKey,
+      poolAssetsMint: assetMint,
+      poolSharesMint: shareMint,
+      lbpFactorySetting: lbpFactoryPda,
+    };
+    return {
+      assetsIn: await program.methods.previewAssetsIn(amount).accounts(accounts).view(),
+      sharesOut: await program.methods.previewSharesOut(amount).accounts(accounts).view(),
+      assetsOut: await program.methods.previewAssetsOut(amount).accounts(accounts).view(),
+      sharesIn: await program.methods.previewSharesIn(amount).accounts(accounts).view(),
+    };
+  };
+
+  it("should reject a zero max share price", async () => {
+    try {
+      await createPricedPool(new anchor.BN(0));
+      expect.fail("zero max share price should be rejected");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Max Share Price");
+    }
+  });
+
+  it("should leave prices below the cap untouched", async () => {
+    // ten assets per share is far above the spot price
+    const capped = await previewAll(await createPricedPool(SOL.muln(10)), SOL);
+    const reference = await previewAll(await createPricedPool(SOL.muln(10_000)), SOL);
+
+    assert.ok(capped.assetsIn.eq(reference.assetsIn), "assets in");
+    assert.ok(capped.sharesOut.eq(reference.sharesOut), "shares out");
+    assert.ok(capped.assetsOut.eq(reference.assetsOut), "assets out");
+    assert.ok(capped.sharesIn.eq(reference.sharesIn), "shares in");
+
+    // the curve price is around one asset per share
+    assert.ok(capped.assetsIn.gt(SOL) && capped.assetsIn.lt(SOL.muln(2)), "assets in follows the curve");
+    assert.ok(capped.assetsOut.lt(SOL) && capped.assetsOut.gt(SOL.divn(2)), "assets out follows the curve");
+  });
+
+  it("should clamp the price to the cap in every direction", async () => {
+    // half an asset per share is below the spot price
+    const { assetsIn, sharesOut, assetsOut, sharesIn } = await previewAll(await createPricedPool(SOL.divn(2)), SOL);
+
+    assert.ok(assetsIn.eq(SOL.divn(2)), `buying a share costs the cap, got ${assetsIn}`);
+    // buys are priced net of the swap fee
+    const netAssetsIn = SOL.sub(SOL.mul(await getSwapFees(lbpFactoryPda)).div(SOL));
+    assert.ok(sharesOut.eq(netAssetsIn.muln(2)), `an asset buys two shares, got ${sharesOut}`);
+    assert.ok(assetsOut.eq(SOL.divn(2)), `selling a share pays the cap, got ${assetsOut}`);
+    assert.ok(sharesIn.eq(SOL.muln(2)), `an asset costs two shares, got ${sharesIn}`);
+  });
+
+  it("should round the capped amounts in favor of the pool", async () => {
+    // a third of an asset per share does not divide evenly
+    const maxSharePrice = SOL.divn(3);
+    const amount = new anchor.BN(10);
+    const { assetsIn, sharesOut, assetsOut, sharesIn } = await previewAll(await createPricedPool(maxSharePrice), amount);
+
+    assert.ok(assetsIn.eq(amount.mul(maxSharePrice).add(SOL.subn(1)).div(SOL)), `assets in rounds up, got ${assetsIn}`);
+    assert.ok(assetsOut.eq(amount.mul(maxSharePrice).div(SOL)), `assets out rounds down, got ${assetsOut}`);
+    assert.ok(sharesOut.eq(amount.mul(SOL).div(maxSharePrice)), `shares out rounds down, got ${sharesOut}`);
+    assert.ok(sharesIn.eq(amount.mul(SOL).add(maxSharePrice.subn(1)).div(maxSharePrice)), `shares in rounds up, got ${sharesIn}`);
+  });
+
+  it("should swap at the capped price", async () => {
+    const { pool, assetVault, shareVault } = await createPricedPool(SOL.divn(2));
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+    const netAssetsIn = SOL.sub(SOL.mul(await getSwapFees(lbpFactoryPda)).div(SOL));
+    assert.ok(sharesOut.eq(netAssetsIn.muln(2)), `bought at the cap, got ${sharesOut}`);
+  });
+});
@@ -0,0 +1,226 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  ZERO_ADDRESS,
+  fund,
+  initialize,
+  program,
+  provider,
+} from "./utils";
+
+describe("Factory Ownership Transfer", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1400);
+
+  /* Global Variables */
+  let lbpFactoryPda;
+  let authority;
+  let newAuthority;
+  let stranger;
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    authority = anchor.web3.Keypair.generate();
+    await fund(authority.publicKey);
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, provider.wallet.publicKey, authority);
+  });
+
+  beforeEach(async () => {
+    newAuthority = anchor.web3.Keypair.generate();
+    await fund(newAuthority.publicKey);
+
+    stranger = anchor.web3.Keypair.generate();
+    await fund(stranger.publicKey);
+  });
+
+  const withEvent = async (name: string, fn: () => Promise<any>) => {
+    let emitted = null;
+    const id = program.addEventListener(name, (event, slot) => {
+      emitted = event;
+    });
+    try {
+      await fn();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return emitted;
+  };
+
+  const transferOwnership = (signer, newOwner) =>
+    program.methods
+      .transferOwnership(newOwner)
+      .accounts({
+        authority: signer.publicKey,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+  const acceptOwnership = (signer) =>
+    program.methods
+      .acceptOwnership()
+      .accounts({
+        pendingAuthority: signer.publicKey,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+  const cancelOwnershipTransfer = (signer) =>
+    program.methods
+      .cancelOwnershipTransfer()
+      .accounts({
+        authority: signer.publicKey,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+  const setSwapFee = (signer, newFee) =>
+    program.methods
+      .setSwapFee(newFee)
+      .accounts({
+        authority: signer.publicKey,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .signers([signer])
+      .rpc();
+
+  it("should start with no pending authority", async () => {
+    const settings = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    assert.ok(settings.authority.equals(authority.publicKey));
+    assert.ok(settings.pendingAuthority.equals(ZERO_ADDRESS));
+  });
+
+  it("should only let the authority start a transfer", async () => {
+    try {
+      await transferOwnership(stranger, newAuthority.publicKey);
+      expect.fail("stranger should not start a transfer");
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    try {
+      await transferOwnership(authority, ZERO_ADDRESS);
+      expect.fail("default pubkey should be rejected");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Authority");
+    }
+  });
+
+  it("should set a pending authority without changing the authority", async () => {
+    const event = await withEvent("OwnershipTransferStarted", () =>
+      transferOwnership(authority, newAuthority.publicKey)
+    );
+
+    const settings = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    assert.ok(settings.authority.equals(authority.publicKey), "authority unchanged");
+    assert.ok(settings.pendingAuthority.equals(newAuthority.publicKey), "pending authority set");
+    assert.ok(event && event.previousAuthority.equals(authority.publicKey));
+    assert.ok(event.newAuthority.equals(newAuthority.publicKey));
+
+    // the current authority keeps its rights until the transfer is accepted
+    await setSwapFee(authority, new anchor.BN(1000));
+
+    await cancelOwnershipTransfer(authority);
+  });
+
+  it("should only let the pending authority accept", async () => {
+    await transferOwnership(authority, newAuthority.publicKey);
+
+    try {
+      await acceptOwnership(stranger);
+      expect.fail("stranger should not accept");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+
+    await cancelOwnershipTransfer(authority);
+  });
+
+  it("should only let the authority cancel a pending transfer", async () => {
+    try {
+      await cancelOwnershipTransfer(authority);
+      expect.fail("cancel without a pending transfer should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("No pending ownership transfer");
+    }
+
+    await transferOwnership(authority, newAuthority.publicKey);
+
+    try {
+      await cancelOwnershipTransfer(newAuthority);
+      expect.fail("pending authority should not cancel");
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    const event = await withEvent("OwnershipTransferCancelled", () =>
+      cancelOwnershipTransfer(authority)
+    );
+
+    const settings = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    assert.ok(settings.authority.equals(authority.publicKey), "authority unchanged");
+    assert.ok(settings.pendingAuthority.equals(ZERO_ADDRESS), "pending authority cleared");
+    assert.ok(event && event.authority.equals(authority.publicKey), "event authority");
+    assert.ok(event.cancelledAuthority.equals(newAuthority.publicKey), "event cancelled authority");
+
+    try {
+      await acceptOwnership(newAuthority);
+      expect.fail("cancelled transfer should not be accepted");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+  });
+
+  it("should let a newer proposal replace the pending authority", async () => {
+    await transferOwnership(authority, stranger.publicKey);
+    await transferOwnership(authority, newAuthority.publicKey);
+
+    try {
+      await acceptOwnership(stranger);
+      expect.fail("replaced proposal should not be accepted");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+
+    await cancelOwnershipTransfer(authority);
+  });
+
+  it("should hand over the authority on accept", async () => {
+    await transferOwnership(authority, newAuthority.publicKey);
+
+    const event = await withEvent("OwnershipTransferred", () =>
+      acceptOwnership(newAuthority)
+    );
+
+    const settings = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    assert.ok(settings.authority.equals(newAuthority.publicKey), "authority changed");
+    assert.ok(settings.pendingAuthority.equals(ZERO_ADDRESS), "pending authority cleared");
+    assert.ok(event && event.previousAuthority.equals(authority.publicKey));
+    assert.ok(event.newAuthority.equals(newAuthority.publicKey));
+
+    // the previous authority lost its rights
+    try {
+      await setSwapFee(authority, new anchor.BN(1000));
+      expect.fail("previous authority should not set fees");
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+    await setSwapFee(newAuthority, new anchor.BN(1000));
+
+    // the accept can not be replayed
+    try {
+      await acceptOwnership(newAuthority);
+      expect.fail("accept should not be replayed");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+
+    authority = newAuthority;
+  });
+});
@@ -0,0 +1,275 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  ONE_DAY,
+  TWO_DAYS,
+  ZERO_ADDRESS,
+  createMintAndVault,
+  createPool,
+  createUser,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+} from "./utils";
+
+describe("Pool Ownership and Operators", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1500);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let newOwner;
+  let operator;
+  let stranger;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+
+    newOwner = anchor.web3.Keypair.generate();
+    await fund(newOwner.publicKey);
+    operator = anchor.web3.Keypair.generate();
+    await fund(operator.publicKey);
+    stranger = anchor.web3.Keypair.generate();
+    await fund(stranger.publicKey);
+  });
+
+  const createUpcomingPool = async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.add(ONE_DAY);
+    poolSettings.saleEnd = now.add(TWO_DAYS);
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    return { poolSettings, ...created };
+  };
+
+  const withEvent = async (name: string, fn: () => Promise<any>) => {
+    let emitted = null;
+    const id = program.addEventListener(name, (event, slot) => {
+      emitted = event;
+    });
+    try {
+      await fn();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return emitted;
+  };
+
+  const transferPoolOwnership = (pool, signer, owner) =>
+    program.methods.transferPoolOwnership(owner)
+      .accounts({ pool: pool.publicKey, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const acceptPoolOwnership = (pool, signer) =>
+    program.methods.acceptPoolOwnership()
+      .accounts({ pool: pool.publicKey, pendingOwner: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const setPoolOperator = (pool, signer, newOperator) =>
+    program.methods.setPoolOperator(newOperator)
+      .accounts({ pool: pool.publicKey, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const pausePool = (pool, signer) =>
+    program.methods.pausePool(null)
+      .accounts({ pool: pool.publicKey, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const unpausePool = (pool, signer) =>
+    program.methods.unpausePool(null)
+      .accounts({ pool: pool.publicKey, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const updatePoolSettings = (pool, assetVault, settings, signer) =>
+    program.methods.updatePoolSettings(settings)
+      .accounts({ pool: pool.publicKey, assetVault: assetVault.publicKey, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const expectUnauthorized = async (fn: () => Promise<any>, message: string) => {
+    try {
+      await fn();
+      expect.fail(message);
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+  };
+
+  it("should create pools without a pending owner or operator", async () => {
+    const { pool } = await createUpcomingPool();
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.owner.equals(depositor.publicKey));
+    assert.ok(poolAccount.pendingOwner.equals(ZERO_ADDRESS));
+    assert.ok(poolAccount.operator.equals(ZERO_ADDRESS));
+  });
+
+  it("should transfer ownership once the new owner accepts", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+
+    await expectUnauthorized(
+      () => transferPoolOwnership(pool, stranger, stranger.publicKey),
+      "stranger should not start a transfer"
+    );
+
+    const startedEvent = await withEvent("PoolOwnershipTransferStarted", () =>
+      transferPoolOwnership(pool, depositor, newOwner.publicKey)
+    );
+    assert.ok(startedEvent && startedEvent.pool.equals(pool.publicKey));
+    assert.ok(startedEvent.previousOwner.equals(depositor.publicKey));
+    assert.ok(startedEvent.newOwner.equals(newOwner.publicKey));
+
+    let poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.owner.equals(depositor.publicKey), "owner unchanged until accepted");
+    assert.ok(poolAccount.pendingOwner.equals(newOwner.publicKey), "pending owner set");
+
+    await expectUnauthorized(
+      () => acceptPoolOwnership(pool, stranger),
+      "stranger should not accept"
+    );
+
+    const transferredEvent = await withEvent("PoolOwnershipTransferred", () =>
+      acceptPoolOwnership(pool, newOwner)
+    );
+    assert.ok(transferredEvent && transferredEvent.previousOwner.equals(depositor.publicKey));
+    assert.ok(transferredEvent.newOwner.equals(newOwner.publicKey));
+
+    poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.owner.equals(newOwner.publicKey), "owner changed");
+    assert.ok(poolAccount.pendingOwner.equals(ZERO_ADDRESS), "pending owner cleared");
+
+    // only the new owner administers the pool
+    await expectUnauthorized(
+      () => updatePoolSettings(pool, assetVault, poolSettings, depositor),
+      "previous owner should not update settings"
+    );
+    await updatePoolSettings(pool, assetVault, poolSettings, newOwner);
+
+    await expectUnauthorized(
+      () => acceptPoolOwnership(pool, newOwner),
+      "accept should not be replayed"
+    );
+  });
+
+  it("should reject the default pubkey as new owner", async () => {
+    const { pool } = await createUpcomingPool();
+
+    try {
+      await transferPoolOwnership(pool, depositor, ZERO_ADDRESS);
+      expect.fail("default pubkey should be rejected");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Authority");
+    }
+  });
+
+  it("should let the operator pause and unpause only", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+
+    await expectUnauthorized(
+      () => setPoolOperator(pool, operator, operator.publicKey),
+      "operator can not appoint itself"
+    );
+
+    const operatorEvent = await withEvent("PoolOperatorSet", () =>
+      setPoolOperator(pool, depositor, operator.publicKey)
+    );
+    assert.ok(operatorEvent && operatorEvent.operator.equals(operator.publicKey));
+
+    await pausePool(pool, operator);
+    assert.ok((await program.account.pool.fetch(pool.publicKey)).paused, "paused by operator");
+    await unpausePool(pool, operator);
+    assert.ok(!(await program.account.pool.fetch(pool.publicKey)).paused, "unpaused by operator");
+
+    await expectUnauthorized(
+      () => pausePool(pool, stranger),
+      "stranger should not pause"
+    );
+    await expectUnauthorized(
+      () => updatePoolSettings(pool, assetVault, poolSettings, operator),
+      "operator should not update settings"
+    );
+    await expectUnauthorized(
+      () => transferPoolOwnership(pool, operator, operator.publicKey),
+      "operator should not transfer ownership"
+    );
+    await expectUnauthorized(
+      () => setPoolOperator(pool, operator, stranger.publicKey),
+      "operator should not replace the operator"
+    );
+
+    // the owner keeps every role
+    await pausePool(pool, depositor);
+    await unpausePool(pool, depositor);
+  });
+
+  it("should let the owner remove the operator", async () => {
+    const { pool } = await createUpcomingPool();
+
+    await setPoolOperator(pool, depositor, operator.publicKey);
+    await setPoolOperator(pool, depositor, ZERO_ADDRESS);
+
+    assert.ok((await program.account.pool.fetch(pool.publicKey)).operator.equals(ZERO_ADDRESS));
+    await expectUnauthorized(
+      () => pausePool(pool, operator),
+      "removed operator should not pause"
+    );
+  });
+});
@@ -0,0 +1,247 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  ONE_DAY,
+  SOL,
+  TWO_DAYS,
+  createMintAndVault,
+  createPool,
+  createUser,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  warpTo,
+} from "./utils";
+
+describe("Pool Pause History", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1700);
+  const decimals = 6; // mint decimals
+  const PAUSE_REASON = 7;
+  const PAUSE_SECONDS = new anchor.BN(60); // how long a test pause lasts
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createActivePool = async (extendSaleOnPause: boolean) => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.extendSaleOnPause = extendSaleOnPause;
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    return { poolSettings, ...created };
+  };
+
+  const withEvent = async (name: string, fn: () => Promise<any>) => {
+    let emitted = null;
+    const id = program.addEventListener(name, (event, slot) => {
+      emitted = event;
+    });
+    try {
+      await fn();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return emitted;
+  };
+
+  const computeWeights = (pool, assetVault, shareVault) =>
+    program.methods.computeReservesAndWeights()
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetVault: assetVault.publicKey,
+        poolShareVault: shareVault.publicKey,
+      })
+      .view();
+
+  const pausePool = (pool, reason = null) =>
+    withEvent("PoolPaused", () =>
+      program.methods.pausePool(reason)
+        .accounts({ pool: pool.publicKey, authority: depositor.publicKey })
+        .signers([depositor])
+        .rpc()
+    );
+
+  const unpausePool = (pool, reason = null) =>
+    withEvent("PoolUnpaused", () =>
+      program.methods.unpausePool(reason)
+        .accounts({ pool: pool.publicKey, authority: depositor.publicKey })
+        .signers([depositor])
+        .rpc()
+    );
+
+  it("should emit pause events with signer, timestamp and reason", async () => {
+    const { pool } = await createActivePool(false);
+
+    const pausedEvent = await pausePool(pool, PAUSE_REASON);
+    assert.ok(pausedEvent, "PoolPaused emitted");
+    assert.ok(pausedEvent.pool.equals(pool.publicKey));
+    assert.ok(pausedEvent.signer.equals(depositor.publicKey));
+    assert.equal(pausedEvent.reason, PAUSE_REASON);
+    assert.ok(pausedEvent.timestamp.gtn(0));
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.pausedAt.eq(pausedEvent.timestamp), "pause start recorded");
+
+    // pausing again is a no-op and keeps the original pause start
+    const repeatedEvent = await pausePool(pool);
+    assert.ok(repeatedEvent === null, "no event for a repeated pause");
+
+    const unpausedEvent = await unpausePool(pool);
+    assert.ok(unpausedEvent, "PoolUnpaused emitted");
+    assert.ok(unpausedEvent.signer.equals(depositor.publicKey));
+    assert.equal(unpausedEvent.reason, null);
+    assert.ok(unpausedEvent.pausedSeconds.eqn(0), "unpaused at the same timestamp");
+  });
+
+  it("should accumulate paused seconds across pauses", async () => {
+    const { pool } = await createActivePool(false);
+
+    let total = new anchor.BN(0);
+    for (let i = 0; i < 2; i++) {
+      const pausedEvent = await pausePool(pool);
+      await warpTo(pausedEvent.timestamp.add(PAUSE_SECONDS));
+      const unpausedEvent = await unpausePool(pool);
+      assert.ok(unpausedEvent.pausedSeconds.eq(PAUSE_SECONDS), "paused seconds");
+      total = total.add(unpausedEvent.pausedSeconds);
+    }
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.totalPausedSeconds.eq(PAUSE_SECONDS.muln(2)), "total paused seconds");
+    assert.ok(poolAccount.pausedAt.eqn(0), "pause start cleared");
+  });
+
+  it("should keep sale end unless extension is enabled", async () => {
+    const { pool, poolSettings } = await createActivePool(false);
+
+    const pausedEvent = await pausePool(pool);
+    await warpTo(pausedEvent.timestamp.add(PAUSE_SECONDS));
+    await unpausePool(pool);
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.saleEnd.eq(poolSettings.saleEnd), "sale end unchanged");
+  });
+
+  it("should extend sale end and vesting by the time paused", async () => {
+    const { pool, poolSettings } = await createActivePool(true);
+
+    const pausedEvent = await pausePool(pool);
+    await warpTo(pausedEvent.timestamp.add(PAUSE_SECONDS));
+    const unpausedEvent = await unpausePool(pool);
+
+    const extension = unpausedEvent.pausedSeconds;
+    assert.ok(extension.eq(PAUSE_SECONDS), "extended by the pause");
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.saleEnd.eq(poolSettings.saleEnd.add(extension)), "sale end extended");
+    assert.ok(unpausedEvent.saleEnd.eq(poolAccount.settings.saleEnd), "event reports new sale end");
+    assert.ok(poolAccount.settings.vestCliff.eq(poolSettings.vestCliff.add(extension)), "vest cliff moved");
+    assert.ok(poolAccount.settings.vestEnd.eq(poolSettings.vestEnd.add(extension)), "vest end moved");
+  });
+
+  it("should not extend for time paused before sale start", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.add(ONE_DAY);
+    poolSettings.saleEnd = now.add(TWO_DAYS);
+    poolSettings.extendSaleOnPause = true;
+    const { pool } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const pausedEvent = await pausePool(pool);
+    await warpTo(pausedEvent.timestamp.add(PAUSE_SECONDS));
+    const unpausedEvent = await unpausePool(pool);
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(unpausedEvent.pausedSeconds.eq(PAUSE_SECONDS), "pause still counted");
+    assert.ok(poolAccount.totalPausedSeconds.eq(unpausedEvent.pausedSeconds));
+    assert.ok(poolAccount.settings.saleEnd.eq(poolSettings.saleEnd), "sale end unchanged");
+  });
+
+  it("should give back the sale time left when a pause crosses sale end", async () => {
+    const { pool, poolSettings } = await createActivePool(true);
+    const secondsLeft = new anchor.BN(10);
+
+    await warpTo(poolSettings.saleEnd.sub(secondsLeft));
+    await pausePool(pool);
+    await warpTo(poolSettings.saleEnd.add(ONE_DAY));
+    const unpausedEvent = await unpausePool(pool);
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.saleEnd.eq(unpausedEvent.timestamp.add(secondsLeft)), "sale time left given back");
+    assert.ok(unpausedEvent.saleEnd.eq(poolAccount.settings.saleEnd), "event reports new sale end");
+  });
+
+  it("should hold the weight curve while paused", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.weightStart = SOL.div(new anchor.BN(10)).mul(new anchor.BN(9)); // 90%
+    poolSettings.weightEnd = SOL.div(new anchor.BN(10)); // 10%
+    poolSettings.extendSaleOnPause = true;
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const before = await computeWeights(pool, assetVault, shareVault);
+    const pausedEvent = await pausePool(pool);
+    await warpTo(pausedEvent.timestamp.add(PAUSE_SECONDS));
+    const unpausedEvent = await unpausePool(pool);
+    const after = await computeWeights(pool, assetVault, shareVault);
+
+    assert.ok(after.assetWeight.eq(before.assetWeight), "asset weight resumes where it paused");
+    assert.ok(after.shareWeight.eq(before.shareWeight), "share weight resumes where it paused");
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.saleStart.eq(poolSettings.saleStart.add(PAUSE_SECONDS)), "sale start moved");
+    assert.ok(unpausedEvent.saleStart.eq(poolAccount.settings.saleStart), "event reports new sale start");
+  });
+});
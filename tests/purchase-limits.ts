@@ -0,0 +1,211 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert } from "chai";
+import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  SOL,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  expectError,
+  fund,
+  getDefaultPoolSettings,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+describe("Per-User Purchase Limits", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1900);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let otherBuyer;
+  let otherBuyerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _otherBuyer,
+      userAssetVault: _otherBuyerAssetVault,
+    } = await createUser(assetMint, shareMint);
+    otherBuyer = _otherBuyer;
+    otherBuyerAssetVault = _otherBuyerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createLimitedPool = async (limits) => {
+    const poolSettings = { ...(await getDefaultPoolSettings(assetMint, shareMint)), ...limits };
+    return await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+  };
+
+  const buyExactAssets = (user, userAssetVault, userStats, { pool, assetVault, shareVault }, assetsIn) =>
+    swapExactAssetsForShares(
+      assetsIn,
+      pool,
+      user,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      userAssetVault,
+      lbpFactoryPda,
+      userStats,
+      assetMint,
+      shareMint
+    );
+
+  const buyExactShares = (user, userAssetVault, userStats, { pool, assetVault, shareVault }, sharesOut) =>
+    program.methods.swapAssetsForExactShares(
+      user.publicKey,
+      sharesOut,
+      SOL.mul(new anchor.BN(10)),
+      null,
+      null,
+    ).accounts({
+      depositor: user.publicKey,
+      pool: pool.publicKey,
+      poolAssetsAccount: assetVault.publicKey,
+      poolSharesAccount: shareVault.publicKey,
+      poolAssetsMint: assetMint,
+      poolSharesMint: shareMint,
+      depositorAssetsAccount: userAssetVault,
+      recipientUserStats: userStats,
+      lbpFactorySetting: lbpFactoryPda,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([user])
+    .rpc();
+
+  it("should validate limits on pool creation", async () => {
+    await expectError(
+      () => createLimitedPool({ maxSharesPerUser: new anchor.BN(0) }),
+      "Invalid Purchase Limits",
+      "zero share cap should be rejected"
+    );
+
+    await expectError(
+      () => createLimitedPool({ minAssetsPerBuy: SOL.mul(new anchor.BN(2)), maxAssetsPerUser: SOL }),
+      "Invalid Purchase Limits",
+      "minimum buy above the asset cap should be rejected"
+    );
+  });
+
+  it("should reject buys below the minimum size", async () => {
+    const created = await createLimitedPool({ minAssetsPerBuy: SOL });
+    const { userStats: buyerStats } = await createUserStats(created.pool.publicKey, buyer);
+
+    await expectError(
+      () => buyExactAssets(buyer, buyerAssetVault, buyerStats, created, SOL.subn(1)),
+      "Min Assets Per Buy Not Met",
+      "buy below the minimum should fail"
+    );
+
+    await buyExactAssets(buyer, buyerAssetVault, buyerStats, created, SOL);
+  });
+
+  it("should cap the shares each user can buy", async () => {
+    const created = await createLimitedPool({ maxSharesPerUser: SOL.mul(new anchor.BN(2)) });
+    const { userStats: buyerStats } = await createUserStats(created.pool.publicKey, buyer);
+    const { userStats: otherBuyerStats } = await createUserStats(created.pool.publicKey, otherBuyer);
+
+    await buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL);
+    await buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL);
+
+    await expectError(
+      () => buyExactShares(buyer, buyerAssetVault, buyerStats, created, new anchor.BN(1)),
+      "Max Shares Per User Exceeded",
+      "buy above the share cap should fail"
+    );
+
+    // the cap applies per user
+    await buyExactShares(otherBuyer, otherBuyerAssetVault, otherBuyerStats, created, SOL);
+  });
+
+  it("should cap the assets each user can spend", async () => {
+    const created = await createLimitedPool({ maxAssetsPerUser: SOL.mul(new anchor.BN(3)).divn(2) });
+    const { userStats: buyerStats } = await createUserStats(created.pool.publicKey, buyer);
+
+    await buyExactAssets(buyer, buyerAssetVault, buyerStats, created, SOL);
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.assetsSpent.eq(SOL), "assets spent recorded");
+
+    await expectError(
+      () => buyExactAssets(buyer, buyerAssetVault, buyerStats, created, SOL),
+      "Max Assets Per User Exceeded",
+      "buy above the asset cap should fail"
+    );
+
+    await expectError(
+      () => buyExactShares(buyer, buyerAssetVault, buyerStats, created, SOL),
+      "Max Assets Per User Exceeded",
+      "exact shares buy above the asset cap should fail"
+    );
+
+    await buyExactAssets(buyer, buyerAssetVault, buyerStats, created, SOL.divn(2));
+  });
+});
@@ -0,0 +1,265 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  SALE_WINDOW,
+  SOL,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  expectError,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  getVaultBalance,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+  warpTo,
+} from "./utils";
+
+describe("Redeem For", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(2400);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyers;
+  let cranker;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // anyone can crank
+    cranker = anchor.web3.Keypair.generate();
+    await fund(cranker.publicKey);
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    buyers = [await createUser(assetMint, shareMint), await createUser(assetMint, shareMint)];
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const withEvents = async (name: string, fn: () => Promise<any>) => {
+    const emitted = [];
+    const id = program.addEventListener(name, (event, slot) => {
+      emitted.push(event);
+    });
+    try {
+      await fn();
+    } finally {
+      await program.removeEventListener(id);
+    }
+    return emitted;
+  };
+
+  const shareAta = (user: anchor.web3.PublicKey) =>
+    splToken.getAssociatedTokenAddressSync(shareMint, user, false, splToken.TOKEN_PROGRAM_ID);
+
+  // every buyer buys, then the pool closes once vesting ended
+  const buyAndClose = async (close = true) => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleEnd = now.add(SALE_WINDOW);
+    poolSettings.vestCliff = poolSettings.saleEnd;
+    poolSettings.vestEnd = poolSettings.saleEnd.add(new anchor.BN(1));
+
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority } = created;
+
+    const purchases = [];
+    for (const { user, userAssetVault } of buyers) {
+      const { userStats } = await createUserStats(pool.publicKey, user);
+      const { sharesOut } = await swapExactAssetsForShares(
+        SOL,
+        pool,
+        user,
+        shareVault.publicKey,
+        assetVault.publicKey,
+        userAssetVault,
+        lbpFactoryPda,
+        userStats,
+        assetMint,
+        shareMint
+      );
+      purchases.push({ user: user.publicKey, userStats, sharesOut });
+    }
+
+    if (close) {
+      await warpTo(poolSettings.vestEnd);
+      await closePool(
+        pool.publicKey,
+        assetVault.publicKey,
+        assetVaultAuthority,
+        shareVault.publicKey,
+        shareVaultAuthority,
+        depositorAssetVault,
+        depositorShareVault,
+        feeShareVault,
+        feeAssetVault,
+        lbpFactoryPda,
+        depositor
+      );
+    }
+    return { purchases, ...created };
+  };
+
+  const redeemFor = ({ pool, shareVault, shareVaultAuthority }, users) =>
+    program.methods
+      .redeemFor()
+      .accounts({
+        payer: cranker.publicKey,
+        pool: pool.publicKey,
+        shareVault: shareVault.publicKey,
+        shareVaultAuthority: shareVaultAuthority,
+        lbpFactorySetting: lbpFactoryPda,
+        shareMint: shareMint,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(
+        users.flatMap(({ user, userStats, recipient }) => [
+          { pubkey: user, isWritable: false, isSigner: false },
+          { pubkey: userStats, isWritable: true, isSigner: false },
+          { pubkey: recipient ?? shareAta(user), isWritable: true, isSigner: false },
+        ])
+      )
+      .signers([cranker])
+      .rpc();
+
+  it("should redeem every user in the batch to their ATA", async () => {
+    const created = await buyAndClose();
+    const { purchases } = created;
+
+    const events = await withEvents("Redeemed", () => redeemFor(created, purchases));
+
+    for (const { user, userStats, sharesOut } of purchases) {
+      assert.ok((await getVaultBalance(shareAta(user))).eq(sharesOut), "shares sent to the ATA");
+      const stats = await program.account.userStats.fetch(userStats);
+      assert.ok(stats.claimed.eq(sharesOut), "everything claimed");
+    }
+
+    assert.equal(events.length, purchases.length, "one event per user");
+    for (const [i, event] of events.entries()) {
+      assert.ok(event.user.equals(purchases[i].user), "event user");
+      assert.ok(event.recipient.equals(shareAta(purchases[i].user)), "event recipient");
+      assert.ok(!event.customRecipient, "always the ATA");
+      assert.ok(event.shares.eq(purchases[i].sharesOut), "event shares");
+      assert.ok(event.totalClaimed.eq(purchases[i].sharesOut), "event total claimed");
+    }
+
+    // users with nothing left are skipped
+    const replayed = await withEvents("Redeemed", () => redeemFor(created, purchases));
+    assert.equal(replayed.length, 0, "nothing redeemed twice");
+    for (const { user, sharesOut } of purchases) {
+      assert.ok((await getVaultBalance(shareAta(user))).eq(sharesOut), "balance unchanged");
+    }
+  });
+
+  it("should use an existing ATA", async () => {
+    const created = await buyAndClose();
+    const [{ user, userStats, sharesOut }] = created.purchases;
+
+    const tx = new anchor.web3.Transaction().add(
+      splToken.createAssociatedTokenAccountInstruction(provider.wallet.publicKey, shareAta(user), user, shareMint)
+    );
+    await provider.sendAndConfirm(tx);
+
+    await redeemFor(created, [{ user, userStats }]);
+    assert.ok((await getVaultBalance(shareAta(user))).eq(sharesOut), "shares sent to the existing ATA");
+  });
+
+  it("should revert when pool not closed", async () => {
+    const created = await buyAndClose(false);
+
+    await expectError(
+      () => redeemFor(created, created.purchases),
+      "Redeeming disallowed",
+      "redeeming before close should fail"
+    );
+  });
+
+  it("should reject accounts that don't belong to the user", async () => {
+    const created = await buyAndClose();
+    const [first, second] = created.purchases;
+
+    await expectError(
+      () => redeemFor(created, []),
+      "Invalid Redeem Accounts",
+      "an empty batch should fail"
+    );
+    await expectError(
+      () => redeemFor(created, [{ user: first.user, userStats: second.userStats }]),
+      "Invalid Redeem Accounts",
+      "stats of another user should fail"
+    );
+    await expectError(
+      () => redeemFor(created, [{ ...first, recipient: buyers[0].userShareVault }]),
+      "Invalid Redeem Accounts",
+      "a vault other than the ATA should fail"
+    );
+
+    const stats = await program.account.userStats.fetch(first.userStats);
+    assert.ok(stats.claimed.eqn(0), "nothing claimed");
+  });
+});
@@ -4,6 +4,7 @@ import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import {
   ONE_DAY,
+  SALE_WINDOW,
   SOL,
   TWO_DAYS,
   closePool,
@@ -21,7 +22,8 @@ import {
   program,
   provider,
   swapExactAssetsForShares,
-  getVaultBalance
+  getVaultBalance,
+  warpTo
 } from "./utils";
 
 describe("Redeem And Close Tests", () => {
@@ -118,6 +120,7 @@ describe("Redeem And Close Tests", () => {
 
   it("should close and transfer assets and fees", async () => {
     const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.saleEnd = (await getNow()).add(SALE_WINDOW);
     
     const {
       pool,
@@ -166,7 +169,8 @@ describe("Redeem And Close Tests", () => {
     const feeRecipientAssetVaultBalanceBeforeClose = await getVaultBalance(feeAssetVault);
     const feeRecipientShareVaultBalanceBeforeClose = await getVaultBalance(feeShareVault);
 
-    // close the pool
+    // close the pool once the sale has ended
+    await warpTo(poolSettings.saleEnd);
     await closePool(
       pool.publicKey,
       assetVault.publicKey,
@@ -177,7 +181,8 @@ describe("Redeem And Close Tests", () => {
       depositorShareVault, // now the pool onwer is the depositor
       feeShareVault,
       feeAssetVault,
-      lbpFactoryPda
+      lbpFactoryPda,
+      depositor
     );
 
     const poolOwnerAssetVaultBalanceAfterClose = await getVaultBalance(depositorAssetVault);
@@ -214,7 +219,7 @@ describe("Redeem And Close Tests", () => {
 
     try {
       await program.methods
-        .redeem()
+        .redeem(null, buyerShareVault)
         .accounts({
           user: buyer.publicKey,
           pool: pool.publicKey,
@@ -223,6 +228,7 @@ describe("Redeem And Close Tests", () => {
           lbpFactorySetting:lbpFactoryPda,
           buyerStats: buyerStats,
           recipientShareVault: buyerShareVault,
+          shareMint: shareMint,
           tokenProgram: splToken.TOKEN_PROGRAM_ID,
           rent: SYSVAR_RENT_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -237,10 +243,9 @@ describe("Redeem And Close Tests", () => {
   it("should redeem all after vest end", async () => {
     const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
     const now = await getNow();
-    poolSettings.vestCliff = now.sub(TWO_DAYS).sub(TWO_DAYS);
-    poolSettings.vestEnd = now.sub(TWO_DAYS); // vest end just passed
-    poolSettings.saleStart = now.sub(TWO_DAYS).sub(TWO_DAYS).sub(TWO_DAYS);
-    poolSettings.saleEnd = now.sub(TWO_DAYS).sub(TWO_DAYS).sub(ONE_DAY);
+    poolSettings.saleEnd = now.add(SALE_WINDOW);
+    poolSettings.vestCliff = poolSettings.saleEnd;
+    poolSettings.vestEnd = poolSettings.saleEnd.add(new anchor.BN(1));
 
     const {
       pool,
@@ -269,7 +274,8 @@ describe("Redeem And Close Tests", () => {
       shareMint
     );
 
-    // close the pool
+    // close the pool after vest end
+    await warpTo(poolSettings.vestEnd);
     await closePool(
       pool.publicKey,
       assetVault.publicKey,
@@ -280,14 +286,15 @@ describe("Redeem And Close Tests", () => {
       depositorShareVault, // now the pool onwer is the depositor
       feeShareVault,
       feeAssetVault,
-      lbpFactoryPda
+      lbpFactoryPda,
+      depositor
     );
 
     let buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
     const userClaimedBefore = buyerStatsAccount.claimed;
 
     await program.methods
-      .redeem()
+      .redeem(null, buyerShareVault)
       .accounts({
         user: buyer.publicKey,
         pool: pool.publicKey,
@@ -296,6 +303,7 @@ describe("Redeem And Close Tests", () => {
         lbpFactorySetting:lbpFactoryPda,
         buyerStats: buyerStats,
         recipientShareVault: buyerShareVault,
+        shareMint: shareMint,
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -312,5 +320,134 @@ describe("Redeem And Close Tests", () => {
       "user claimed after"
     );
   });
-});
 
+  // buys and closes the pool once vesting ended, so every purchased share is claimable
+  const buyAndCloseAfterVest = async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleEnd = now.add(SALE_WINDOW);
+    poolSettings.vestCliff = poolSettings.saleEnd;
+    poolSettings.vestEnd = poolSettings.saleEnd.add(new anchor.BN(1));
+
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority } = created;
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    await warpTo(poolSettings.vestEnd);
+    await closePool(
+      pool.publicKey,
+      assetVault.publicKey,
+      assetVaultAuthority,
+      shareVault.publicKey,
+      shareVaultAuthority,
+      depositorAssetVault,
+      depositorShareVault,
+      feeShareVault,
+      feeAssetVault,
+      lbpFactoryPda,
+      depositor
+    );
+    return { buyerStats, sharesOut, ...created };
+  };
+
+  const redeemTo = ({ pool, shareVault, shareVaultAuthority, buyerStats }, recipientShareVault, recipient = null) =>
+    program.methods
+      .redeem(null, recipient)
+      .accounts({
+        user: buyer.publicKey,
+        pool: pool.publicKey,
+        shareVault: shareVault.publicKey,
+        shareVaultAuthority: shareVaultAuthority,
+        lbpFactorySetting: lbpFactoryPda,
+        buyerStats: buyerStats,
+        recipientShareVault: recipientShareVault,
+        shareMint: shareMint,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+
+  const withEvent = async (name: string, fn: () => Promise<any>) => {
+    let emitted = null;
+    const id = program.addEventListener(name, (event, slot) => {
+      emitted = event;
+    });
+    try {
+      await fn();
+    } finally {
+      await program.removeEventListener(id);
+    }
+    return emitted;
+  };
+
+  const expectInvalidRecipient = async (fn: () => Promise<any>, message: string) => {
+    try {
+      await fn();
+      expect.fail(message);
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Recipient Share Vault");
+    }
+  };
+
+  it("should redeem to the user's ATA by default", async () => {
+    const created = await buyAndCloseAfterVest();
+    const buyerAta = splToken.getAssociatedTokenAddressSync(shareMint, buyer.publicKey, false, splToken.TOKEN_PROGRAM_ID);
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      splToken.createAssociatedTokenAccountInstruction(provider.wallet.publicKey, buyerAta, buyer.publicKey, shareMint)
+    ));
+
+    const event = await withEvent("Redeemed", () => redeemTo(created, buyerAta));
+
+    assert.ok((await getVaultBalance(buyerAta)).eq(created.sharesOut), "shares sent to the ATA");
+    assert.ok(event.user.equals(buyer.publicKey), "event user");
+    assert.ok(event.recipient.equals(buyerAta), "event recipient");
+    assert.ok(!event.customRecipient, "default recipient");
+    assert.ok(event.shares.eq(created.sharesOut), "event shares");
+  });
+
+  it("should only redeem elsewhere with an explicit recipient", async () => {
+    const created = await buyAndCloseAfterVest();
+
+    await expectInvalidRecipient(
+      () => redeemTo(created, redeemRecipientShareVault),
+      "a vault other than the ATA should need an explicit recipient"
+    );
+    await expectInvalidRecipient(
+      () => redeemTo(created, redeemRecipientShareVault, buyerShareVault),
+      "the explicit recipient should match the vault"
+    );
+
+    const event = await withEvent("Redeemed", () => redeemTo(created, redeemRecipientShareVault, redeemRecipientShareVault));
+
+    assert.ok((await getVaultBalance(redeemRecipientShareVault)).eq(created.sharesOut), "shares sent to the recipient");
+    assert.ok(event.user.equals(buyer.publicKey), "event user");
+    assert.ok(event.recipient.equals(redeemRecipientShareVault), "event recipient");
+    assert.ok(event.customRecipient, "overridden recipient");
+  });
+
+  it("should reject a recipient holding another mint", async () => {
+    const created = await buyAndCloseAfterVest();
+
+    await expectInvalidRecipient(
+      () => redeemTo(created, buyerAssetVault, buyerAssetVault),
+      "an asset vault should not receive shares"
+    );
+
+    const buyerStatsAccount = await program.account.userStats.fetch(created.buyerStats);
+    assert.ok(buyerStatsAccount.claimed.eqn(0), "nothing claimed");
+  });
+});
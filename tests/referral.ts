@@ -0,0 +1,235 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert, expect } from "chai";
+import {
+  SOL,
+  SALE_WINDOW,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createReferrerStats,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  getVaultBalance,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+  warpTo,
+} from "./utils";
+
+describe("Referral Fees", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(900);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let referrer;
+  let referrerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const { user: _buyer, userAssetVault: _buyerAssetVault } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const { user: _referrer, userAssetVault: _referrerAssetVault } = await createUser(assetMint, shareMint);
+    referrer = _referrer;
+    referrerAssetVault = _referrerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const claimReferrerFees = async (pool, assetVault, assetVaultAuthority, referrerStats) => {
+    await program.methods.claimReferrerFees().accounts({
+      referrer: referrer.publicKey,
+      pool: pool.publicKey,
+      assetVault: assetVault.publicKey,
+      assetVaultAuthority,
+      referrerStats,
+      referrerAssetVault,
+      assetMint,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+    })
+    .signers([referrer])
+    .rpc();
+  };
+
+  it("should accrue referral fees, exclude them from close and let the referrer claim", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.saleEnd = (await getNow()).add(SALE_WINDOW);
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+      shareVaultAuthority,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { referrerStats } = await createReferrerStats(pool.publicKey, referrer.publicKey);
+
+    const assetsIn = SOL;
+    await swapExactAssetsForShares(
+      assetsIn,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint,
+      referrer.publicKey
+    );
+
+    const factory = await program.account.lbpFactorySetting.fetch(lbpFactoryPda);
+    const swapFee = assetsIn.mul(factory.swapFee).div(SOL);
+    const expectedReferred = assetsIn.sub(swapFee).mul(factory.referrerFee).div(SOL);
+
+    const referrerStatsAccount = await program.account.referrerStats.fetch(referrerStats);
+    assert.ok(referrerStatsAccount.accrued.eq(expectedReferred), "referrer accrued");
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.totalReferred.eq(expectedReferred), "pool total referred");
+
+    // referral fees can only be claimed once the pool is closed
+    try {
+      await claimReferrerFees(pool, assetVault, assetVaultAuthority, referrerStats);
+      expect.fail("claim before close should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Claiming disallowed");
+    }
+
+    const assets = await getVaultBalance(assetVault.publicKey);
+    const totalAssets = assets.sub(poolAccount.totalSwapFeesAsset).sub(expectedReferred);
+    const platformFees = totalAssets.mul(factory.platformFee).div(SOL);
+    const ownerAssetsBefore = await getVaultBalance(depositorAssetVault);
+
+    await warpTo(poolSettings.saleEnd);
+    await closePool(
+      pool.publicKey,
+      assetVault.publicKey,
+      assetVaultAuthority,
+      shareVault.publicKey,
+      shareVaultAuthority,
+      depositorAssetVault,
+      depositorShareVault,
+      feeShareVault,
+      feeAssetVault,
+      lbpFactoryPda,
+      depositor
+    );
+
+    const ownerAssetsAfter = await getVaultBalance(depositorAssetVault);
+    assert.ok(ownerAssetsAfter.sub(ownerAssetsBefore).eq(totalAssets.sub(platformFees)), "owner proceeds exclude referral fees");
+    assert.ok((await getVaultBalance(assetVault.publicKey)).eq(expectedReferred), "referral fees stay in the vault");
+
+    const referrerAssetsBefore = await getVaultBalance(referrerAssetVault);
+    await claimReferrerFees(pool, assetVault, assetVaultAuthority, referrerStats);
+    const referrerAssetsAfter = await getVaultBalance(referrerAssetVault);
+    assert.ok(referrerAssetsAfter.sub(referrerAssetsBefore).eq(expectedReferred), "referrer claimed");
+
+    try {
+      await claimReferrerFees(pool, assetVault, assetVaultAuthority, referrerStats);
+      expect.fail("second claim should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("No Referrer Fees To Claim");
+    }
+  });
+
+  it("should revert when the buyer refers themselves", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const {
+      pool,
+      assetVault,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    await createReferrerStats(pool.publicKey, buyer.publicKey);
+
+    try {
+      await swapExactAssetsForShares(
+        SOL,
+        pool,
+        buyer,
+        shareVault.publicKey,
+        assetVault.publicKey,
+        buyerAssetVault,
+        lbpFactoryPda,
+        buyerStats,
+        assetMint,
+        shareMint,
+        buyer.publicKey
+      );
+      expect.fail("self referral should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Referrer");
+    }
+  });
+});
@@ -0,0 +1,231 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert, expect } from "chai";
+import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  BN_0,
+  ONE_DAY,
+  SALE_WINDOW,
+  SOL,
+  TWO_DAYS,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+  warpTo,
+} from "./utils";
+
+describe("Sale Window", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1000);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const { user: _buyer, userAssetVault: _buyerAssetVault } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const buy = async (pool, assetVault, shareVault, buyerStats) => {
+    return await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+  };
+
+  const close = async (pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority) => {
+    await closePool(
+      pool.publicKey,
+      assetVault.publicKey,
+      assetVaultAuthority,
+      shareVault.publicKey,
+      shareVaultAuthority,
+      depositorAssetVault,
+      depositorShareVault,
+      feeShareVault,
+      feeAssetVault,
+      lbpFactoryPda,
+      depositor
+    );
+  };
+
+  it("should revert buy before sale start", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.add(ONE_DAY);
+    poolSettings.saleEnd = now.add(TWO_DAYS);
+
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    try {
+      await buy(pool, assetVault, shareVault, buyerStats);
+      expect.fail("buy before sale start should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Trading not started");
+    }
+  });
+
+  it("should revert buy and sell after sale end", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.sub(TWO_DAYS);
+    poolSettings.saleEnd = now.sub(ONE_DAY);
+
+    const { pool, assetVault, assetVaultAuthority, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    try {
+      await buy(pool, assetVault, shareVault, buyerStats);
+      expect.fail("buy after sale end should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Trading ended");
+    }
+
+    try {
+      await program.methods.swapExactSharesForAssets(
+        buyer.publicKey,
+        SOL,
+        BN_0,
+      ).accounts({
+        depositor: buyer.publicKey,
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        assetVaultAuthority: assetVaultAuthority,
+        poolSharesAccount: shareVault.publicKey,
+        recipientAssetsAccount: buyerAssetVault,
+        buyerStats: buyerStats,
+        lbpFactorySetting: lbpFactoryPda,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+      expect.fail("sell after sale end should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Trading ended");
+    }
+  });
+
+  it("should only close after sale end", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.saleEnd = (await getNow()).add(SALE_WINDOW);
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+      shareVaultAuthority,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    // trading is open during the sale
+    const { sharesOut } = await buy(pool, assetVault, shareVault, buyerStats);
+    assert.ok(sharesOut.gt(BN_0), "shares out");
+
+    try {
+      await close(pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority);
+      expect.fail("close before sale end should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Closing disallowed");
+    }
+
+    await warpTo(poolSettings.saleEnd);
+
+    try {
+      await buy(pool, assetVault, shareVault, buyerStats);
+      expect.fail("buy after sale end should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Trading ended");
+    }
+
+    await close(pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority);
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.closed, "pool closed");
+  });
+});
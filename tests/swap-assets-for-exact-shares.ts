@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import * as splToken from "@solana/spl-token";
 import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares, getVaultBalance } from "./utils";
 
 describe("swap assets for exact shares", () => {
   /* Settings */
@@ -129,6 +129,8 @@ describe("swap assets for exact shares", () => {
       buyer.publicKey,
       sharesOut,
       maxAssetsIn,
+      null,
+      null,
     ).accounts({
       depositor: buyer.publicKey,
       pool: pool.publicKey,
@@ -150,7 +152,7 @@ describe("swap assets for exact shares", () => {
       const assetsIn = buyEvent.assets;
       const sharesOut = buyEvent.shares;
 
-      const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+      const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
       assert.ok(poolAssetAmount == new anchor.BN(assetsIn).add(defaultInitialAssetAmount).toString(), "pool asset amount");
       assert.ok(maxAssetsIn.toString() == assetsIn, "max assets in");
       const lbpAccount = await program.account.pool.fetch(pool.publicKey);
@@ -207,6 +209,8 @@ describe("swap assets for exact shares", () => {
       sharesRecipient.publicKey,
       sharesOut,
       maxAssetsIn,
+      null,
+      null,
     ).accounts({
       depositor: buyer.publicKey,
       pool: pool.publicKey,
@@ -228,7 +232,7 @@ describe("swap assets for exact shares", () => {
       const assetsIn = buyEvent.assets;
       const sharesOut = buyEvent.shares;
 
-      const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+      const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
       assert.ok(poolAssetAmount == new anchor.BN(assetsIn).add(defaultInitialAssetAmount).toString(), "pool asset amount");
       assert.ok(maxAssetsIn.toString() == assetsIn, "max assets in");
       const lbpAccount = await program.account.pool.fetch(pool.publicKey);
@@ -282,6 +286,8 @@ describe("swap assets for exact shares", () => {
       buyer.publicKey,
       sharesOut,
       maxAssetsIn,
+      null,
+      null,
     ).accounts({
       depositor: buyer.publicKey,
       pool: pool.publicKey,
@@ -330,6 +336,8 @@ describe("swap assets for exact shares", () => {
       buyer.publicKey,
       sharesOut,
       maxAssetsIn2,
+      null,
+      null,
     ).accounts({
       depositor: buyer.publicKey,
       pool: pool.publicKey,
@@ -351,7 +359,7 @@ describe("swap assets for exact shares", () => {
       const assetsIn2 = buyEvent.assets;
       const sharesOut2 = buyEvent.shares;
 
-      const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+      const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
       assert.ok(poolAssetAmount == new anchor.BN(assetsIn1).add(defaultInitialAssetAmount).add(assetsIn2).toString(), "assetsIn");
       assert.ok(maxAssetsIn2.toString() == assetsIn2, "assetsIn");
 
@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import * as splToken from "@solana/spl-token";
 import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares, getVaultBalance } from "./utils";
 
 describe("swap exact assets for shares", () => {
   /* Settings */
@@ -127,6 +127,8 @@ describe("swap exact assets for shares", () => {
         buyer.publicKey,
         assetsIn,
         minSharesOut,
+        null,
+        null,
     ).accounts({
         depositor: buyer.publicKey,
         pool: pool.publicKey,
@@ -148,7 +150,7 @@ describe("swap exact assets for shares", () => {
       const assetsIn = buyEvent.assets;
       const sharesOut = buyEvent.shares;
 
-      const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+      const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
       assert.ok(poolAssetAmount == new anchor.BN(assetsIn).add(defaultInitialAssetAmount).toString(), "assetsIn");
       assert.ok(sharesOut == minSharesOut.toString(), "sharesOut"); 
 
@@ -204,6 +206,8 @@ describe("swap exact assets for shares", () => {
         sharesRecipient.publicKey,
         assetsIn,
         minSharesOut,
+        null,
+        null,
     ).accounts({
         depositor: buyer.publicKey,
         pool: pool.publicKey,
@@ -225,7 +229,7 @@ describe("swap exact assets for shares", () => {
       const assetsIn = buyEvent.assets;
       const sharesOut = buyEvent.shares;
 
-      const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+      const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
       assert.ok(poolAssetAmount == new anchor.BN(assetsIn).add(defaultInitialAssetAmount).toString(), "assetsIn");
       assert.ok(sharesOut == minSharesOut.toString(), "sharesOut"); 
 
@@ -277,6 +281,8 @@ describe("swap exact assets for shares", () => {
         buyer.publicKey,
         assetsIn,
         minSharesOut,
+        null,
+        null,
     ).accounts({
         depositor: buyer.publicKey,
         pool: pool.publicKey,
@@ -321,6 +327,8 @@ describe("swap exact assets for shares", () => {
       buyer.publicKey,
       assetsIn,
       minSharesOut2,
+      null,
+      null,
     ).accounts({
       depositor: buyer.publicKey,
       pool: pool.publicKey,
@@ -342,7 +350,7 @@ describe("swap exact assets for shares", () => {
         const assetsIn2 = buyEvent.assets;
         const sharesOut2 = buyEvent.shares;
   
-        const poolAssetAmount = (await provider.connection.getTokenAccountBalance(assetVault.publicKey)).value.amount;
+        const poolAssetAmount = (await getVaultBalance(assetVault.publicKey)).toString();
         assert.ok(poolAssetAmount == new anchor.BN(assetsIn1).add(defaultInitialAssetAmount).add(assetsIn2).toString(), "assetsIn");
         assert.ok(minSharesOut2.toString() == sharesOut2, "sharesOut");
   
@@ -358,5 +366,73 @@ describe("swap exact assets for shares", () => {
     program.removeEventListener(id);
 
   });
-  
+
+  it("test exact-in and exact-out buys of the same net assets get the same shares", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+
+    // two identical pools, each buy runs on the same state
+    const exactOut = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    poolId = poolId.add(new anchor.BN(1));
+    const exactIn = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    let buyEvent = null;
+    const id = program.addEventListener('Buy', (event, slot) => {
+      buyEvent = event;
+    });
+
+    const { userStats: exactOutStats } = await createUserStats(exactOut.pool.publicKey, buyer);
+    const sharesOut = SOL;
+    try {
+      await program.methods.swapAssetsForExactShares(
+        buyer.publicKey,
+        sharesOut,
+        SOL.muln(2),
+        null,
+        null,
+      ).accounts({
+        depositor: buyer.publicKey,
+        pool: exactOut.pool.publicKey,
+        poolAssetsAccount: exactOut.assetVault.publicKey,
+        poolSharesAccount: exactOut.shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        depositorAssetsAccount: buyerAssetVault,
+        recipientUserStats: exactOutStats,
+        lbpFactorySetting:lbpFactoryPda,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    } finally {
+      program.removeEventListener(id);
+    }
+    const netAssetsIn = buyEvent.assets.sub(buyEvent.swapFee);
+
+    // exact-in takes the fee out of the deposit, find the deposit that leaves the same net assets
+    const swapFee = await getSwapFees(lbpFactoryPda);
+    let assetsIn = netAssetsIn;
+    while (!assetsIn.sub(assetsIn.mul(swapFee).div(SOL)).eq(netAssetsIn)) {
+      assetsIn = netAssetsIn.add(assetsIn.mul(swapFee).div(SOL));
+    }
+
+    const { userStats: exactInStats } = await createUserStats(exactIn.pool.publicKey, buyer);
+    const { sharesOut: exactInSharesOut } = await swapExactAssetsForShares(
+      assetsIn,
+      exactIn.pool,
+      buyer,
+      exactIn.shareVault.publicKey,
+      exactIn.assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      exactInStats,
+      assetMint,
+      shareMint
+    );
+
+    // the exact-out price rounds up, scaling to the mint decimals can leave one share unit over
+    assert.ok(exactInSharesOut.gte(sharesOut), `exact-in bought ${exactInSharesOut}, below ${sharesOut}`);
+    assert.ok(exactInSharesOut.sub(sharesOut).lten(1), `exact-in bought ${exactInSharesOut}, above ${sharesOut}`);
+  });
 });
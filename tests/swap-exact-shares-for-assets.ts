@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import * as splToken from "@solana/spl-token";
 import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares, BN_0, getVaultBalance, warpTo } from "./utils";
 
 describe("swap exact shares for assets", () => {
   /* Settings */
@@ -132,7 +132,7 @@ beforeEach(async () => {
         poolSharesMint: shareMint,
         assetVaultAuthority: assetVaultAuthority,
         poolSharesAccount: shareVault.publicKey,
-        depositorAssetsAccount: buyerAssetVault,
+        recipientAssetsAccount: buyerAssetVault,
         buyerStats: buyerStats,
         lbpFactorySetting:lbpFactoryPda,
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
@@ -146,6 +146,255 @@ beforeEach(async () => {
     }
 
   });
-  
 
-});
\ No newline at end of file
+  const sell = async (pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesIn, limit, recipient = buyer.publicKey, recipientAssetsAccount = buyerAssetVault) => {
+    let sellEvent = null;
+    const id = program.addEventListener("Sell", (event, slot) => {
+      sellEvent = event;
+    });
+
+    try {
+      await program.methods.swapExactSharesForAssets(
+        recipient,
+        sharesIn,
+        limit,
+      ).accounts({
+        depositor: buyer.publicKey,
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        assetVaultAuthority: assetVaultAuthority,
+        poolSharesAccount: shareVault.publicKey,
+        recipientAssetsAccount: recipientAssetsAccount,
+        buyerStats: buyerStats,
+        lbpFactorySetting:lbpFactoryPda,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return sellEvent;
+  };
+
+  it("test sell when selling allowed", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const sharesIn = sharesOut.div(new anchor.BN(2));
+    const preview = await program.methods.previewAssetsOut(
+      sharesIn
+    )
+    .accounts({
+      pool: pool.publicKey,
+      poolAssetsAccount: assetVault.publicKey,
+      poolSharesAccount: shareVault.publicKey,
+      poolAssetsMint: assetMint,
+      poolSharesMint: shareMint,
+      lbpFactorySetting:lbpFactoryPda,
+    })
+    .view();
+    assert.ok(preview.gt(BN_0), "preview should be positive");
+
+    await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesIn, BN_0);
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.lt(sharesOut), "purchased shares should decrease");
+  });
+
+  it("test revert when selling disallowed", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = false;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const sharesIn = sharesOut.div(new anchor.BN(2));
+    try {
+      await program.methods.previewAssetsOut(
+        sharesIn
+      )
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolSharesAccount: shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        lbpFactorySetting:lbpFactoryPda,
+      })
+      .view();
+      expect.fail("preview should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Selling disallowed");
+    }
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesIn, BN_0);
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Selling disallowed");
+    }
+  });
+
+  it("test revert when selling more than purchased", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesOut.add(new anchor.BN(1)), BN_0);
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Insufficient Shares");
+    }
+  });
+
+  it("test sell pays the recipient and records share fees", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const { user: recipient, userAssetVault: recipientAssetVault } = await createUser(assetMint, shareMint);
+    const recipientBalanceBefore = await getVaultBalance(recipientAssetVault);
+
+    const sharesIn = sharesOut.div(new anchor.BN(2));
+    const sellEvent = await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesIn, BN_0, recipient.publicKey, recipientAssetVault);
+
+    assert.ok(sellEvent, "sell event");
+    assert.ok(sellEvent.recipient.equals(recipient.publicKey), "event recipient");
+    assert.ok(sellEvent.shares.eq(sharesIn), "event shares");
+
+    const recipientBalanceAfter = await getVaultBalance(recipientAssetVault);
+    assert.ok(recipientBalanceAfter.sub(recipientBalanceBefore).eq(sellEvent.assets), "recipient received the assets");
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(sellEvent.swapFee.gt(BN_0), "share swap fee");
+    assert.ok(poolAccount.totalSwapFeesShare.eq(sellEvent.swapFee), "share swap fees recorded");
+    assert.ok(poolAccount.totalPurchased.eq(sharesOut.sub(sharesIn)), "total purchased");
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eq(sharesOut.sub(sharesIn)), "seller balance");
+  });
+
+  it("test revert when the payout exceeds the assets not owed as fees", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+    // only virtual assets back the price, and the share price rises over the sale
+    poolSettings.virtualAssets = defaultInitialAssetAmount;
+    poolSettings.weightStart = SOL.muln(9).divn(10);
+    poolSettings.weightEnd = SOL.divn(10);
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint, defaultInitialShareAmount, BN_0);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    await warpTo(poolSettings.saleEnd.subn(1));
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, sharesOut, BN_0);
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Insufficient reserve");
+    }
+  });
+});
@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import * as splToken from "@solana/spl-token";
 import { assert, expect } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, getSwapFees, initialize, program, provider, swapExactAssetsForShares, BN_0, getVaultBalance, warpTo } from "./utils";
 
 describe("swap shares for exact assets", () => {
   /* Settings */
@@ -132,7 +132,7 @@ beforeEach(async () => {
         poolSharesMint: shareMint,
         assetVaultAuthority: assetVaultAuthority,
         poolSharesAccount: shareVault.publicKey,
-        depositorAssetsAccount: buyerAssetVault,
+        recipientAssetsAccount: buyerAssetVault,
         buyerStats: buyerStats,
         lbpFactorySetting:lbpFactoryPda,
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
@@ -146,6 +146,327 @@ beforeEach(async () => {
     }
 
   });
-  
 
-});
\ No newline at end of file
+  const sell = async (pool, assetVault, assetVaultAuthority, shareVault, buyerStats, assetsOut, limit, recipient = buyer.publicKey, recipientAssetsAccount = buyerAssetVault) => {
+    let sellEvent = null;
+    const id = program.addEventListener("Sell", (event, slot) => {
+      sellEvent = event;
+    });
+
+    try {
+      await program.methods.swapSharesForExactAssets(
+        recipient,
+        assetsOut,
+        limit,
+      ).accounts({
+        depositor: buyer.publicKey,
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        assetVaultAuthority: assetVaultAuthority,
+        poolSharesAccount: shareVault.publicKey,
+        recipientAssetsAccount: recipientAssetsAccount,
+        buyerStats: buyerStats,
+        lbpFactorySetting:lbpFactoryPda,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return sellEvent;
+  };
+
+  it("test sell when selling allowed", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const assetsOut = SOL.div(new anchor.BN(10));
+    const preview = await program.methods.previewSharesIn(
+      assetsOut
+    )
+    .accounts({
+      pool: pool.publicKey,
+      poolAssetsAccount: assetVault.publicKey,
+      poolSharesAccount: shareVault.publicKey,
+      poolAssetsMint: assetMint,
+      poolSharesMint: shareMint,
+      lbpFactorySetting:lbpFactoryPda,
+    })
+    .view();
+    assert.ok(preview.gt(BN_0), "preview should be positive");
+
+    await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, assetsOut, sharesOut);
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.lt(sharesOut), "purchased shares should decrease");
+  });
+
+  it("test revert when selling disallowed", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = false;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const assetsOut = SOL.div(new anchor.BN(10));
+    try {
+      await program.methods.previewSharesIn(
+        assetsOut
+      )
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolSharesAccount: shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        lbpFactorySetting:lbpFactoryPda,
+      })
+      .view();
+      expect.fail("preview should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Selling disallowed");
+    }
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, assetsOut, sharesOut);
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Selling disallowed");
+    }
+  });
+
+  it("test revert when selling more than purchased", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, SOL.mul(new anchor.BN(2)), sharesOut.mul(new anchor.BN(10)));
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Insufficient Shares");
+    }
+  });
+
+  it("test sell pays the recipient and records share fees", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    const { user: recipient, userAssetVault: recipientAssetVault } = await createUser(assetMint, shareMint);
+    const recipientBalanceBefore = await getVaultBalance(recipientAssetVault);
+
+    const assetsOut = SOL.div(new anchor.BN(2));
+    const sellEvent = await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, assetsOut, sharesOut, recipient.publicKey, recipientAssetVault);
+
+    assert.ok(sellEvent, "sell event");
+    assert.ok(sellEvent.recipient.equals(recipient.publicKey), "event recipient");
+
+    const recipientBalanceAfter = await getVaultBalance(recipientAssetVault);
+    assert.ok(recipientBalanceAfter.sub(recipientBalanceBefore).eq(assetsOut), "recipient received the assets");
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(sellEvent.swapFee.gt(BN_0), "share swap fee");
+    assert.ok(poolAccount.totalSwapFeesShare.eq(sellEvent.swapFee), "share swap fees recorded");
+    assert.ok(poolAccount.totalPurchased.eq(sharesOut.sub(sellEvent.shares)), "total purchased");
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eq(sharesOut.sub(sellEvent.shares)), "seller balance");
+  });
+
+  it("test exact-out shares sold exact-in pay out at least the assets", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+
+    // two identical pools, each sell runs on the same state
+    const createBoughtPool = async (id) => {
+      const created = await createPool(id, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+      const { userStats: buyerStats } = await createUserStats(created.pool.publicKey, buyer);
+      const { sharesOut } = await swapExactAssetsForShares(
+        SOL,
+        created.pool,
+        buyer,
+        created.shareVault.publicKey,
+        created.assetVault.publicKey,
+        buyerAssetVault,
+        lbpFactoryPda,
+        buyerStats,
+        assetMint,
+        shareMint
+      );
+      return { ...created, buyerStats, sharesOut };
+    };
+    const exactOut = await createBoughtPool(poolId);
+    poolId = poolId.add(new anchor.BN(1));
+    const exactIn = await createBoughtPool(poolId);
+
+    const assetsOut = SOL.div(new anchor.BN(2));
+    const sellEvent = await sell(exactOut.pool, exactOut.assetVault, exactOut.assetVaultAuthority, exactOut.shareVault, exactOut.buyerStats, assetsOut, exactOut.sharesOut);
+
+    let exactInEvent = null;
+    const id = program.addEventListener("Sell", (event, slot) => {
+      exactInEvent = event;
+    });
+    try {
+      await program.methods.swapExactSharesForAssets(
+        buyer.publicKey,
+        sellEvent.shares,
+        assetsOut
+      ).accounts({
+        depositor: buyer.publicKey,
+        pool: exactIn.pool.publicKey,
+        poolAssetsAccount: exactIn.assetVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        assetVaultAuthority: exactIn.assetVaultAuthority,
+        poolSharesAccount: exactIn.shareVault.publicKey,
+        recipientAssetsAccount: buyerAssetVault,
+        buyerStats: exactIn.buyerStats,
+        lbpFactorySetting:lbpFactoryPda,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    } finally {
+      program.removeEventListener(id);
+    }
+
+    assert.ok(exactInEvent.assets.gte(assetsOut), "exact-in pays at least the exact-out assets");
+    assert.ok(exactInEvent.swapFee.lte(sellEvent.swapFee), "exact-in fee does not exceed the exact-out fee");
+  });
+
+  it("test revert when the payout dips into the assets owed as fees", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.sellingAllowed = true;
+    // only virtual assets back the price, and the share price rises over the sale
+    poolSettings.virtualAssets = defaultInitialAssetAmount;
+    poolSettings.weightStart = SOL.muln(9).divn(10);
+    poolSettings.weightEnd = SOL.divn(10);
+
+    const {
+      pool,
+      assetVault,
+      assetVaultAuthority,
+      shareVault,
+    } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint, defaultInitialShareAmount, BN_0);
+
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    await warpTo(poolSettings.saleEnd.subn(1));
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.totalSwapFeesAsset.gt(BN_0), "asset swap fees");
+    const available = (await getVaultBalance(assetVault.publicKey))
+      .sub(poolAccount.totalSwapFeesAsset)
+      .sub(poolAccount.totalReferred);
+
+    try {
+      await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, available.add(new anchor.BN(1)), sharesOut);
+      expect.fail("sell should revert");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Insufficient reserve");
+    }
+
+    await sell(pool, assetVault, assetVaultAuthority, shareVault, buyerStats, available, sharesOut);
+    const vaultAfter = await getVaultBalance(assetVault.publicKey);
+    assert.ok(vaultAfter.eq(poolAccount.totalSwapFeesAsset.add(poolAccount.totalReferred)), "fees left in the vault");
+  });
+});
@@ -0,0 +1,142 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  SOL,
+  createMintAndVault,
+  createPool,
+  createToken2022Mint,
+  createToken2022Vault,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getVaultBalance,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+} from "./utils";
+
+describe("Token-2022", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1200);
+  const decimals = 6; // mint decimals
+  const transferFeeBasisPoints = 100; // 1%
+
+  /* Global Variables */
+  let shareMint;
+
+  let depositor;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare share mint, asset mints are created per test
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    // the depositor only needs a share vault here, asset vaults depend on the asset mint
+    const { user: _depositor, userShareVault: _depositorShareVault } = await createUser(shareMint, shareMint);
+    depositor = _depositor;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createToken2022Pool = async (assetMint) => {
+    const depositorAssetVault = await createToken2022Vault(assetMint, depositor.publicKey, defaultInitialAssetAmount.muln(2));
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    return await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+  };
+
+  it("should price buys on the assets received after the transfer fee", async () => {
+    const assetMint = await createToken2022Mint(decimals, { transferFeeBasisPoints });
+    const { pool, assetVault, shareVault } = await createToken2022Pool(assetMint);
+
+    const buyer = anchor.web3.Keypair.generate();
+    await fund(buyer.publicKey);
+    const buyerAssetVault = await createToken2022Vault(assetMint, buyer.publicKey, SOL.muln(10));
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+
+    const assetsIn = SOL;
+    const transferFee = assetsIn.muln(transferFeeBasisPoints).divn(10_000);
+
+    const expectedSharesOut = await program.methods.previewSharesOut(assetsIn)
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetsAccount: assetVault.publicKey,
+        poolSharesAccount: shareVault.publicKey,
+        poolAssetsMint: assetMint,
+        poolSharesMint: shareMint,
+        lbpFactorySetting: lbpFactoryPda,
+      })
+      .view();
+
+    const vaultBefore = await getVaultBalance(assetVault.publicKey);
+    const { sharesOut } = await swapExactAssetsForShares(
+      assetsIn,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+    const vaultAfter = await getVaultBalance(assetVault.publicKey);
+
+    assert.ok(vaultAfter.sub(vaultBefore).eq(assetsIn.sub(transferFee)), "pool receives the assets net of the transfer fee");
+    assert.ok(sharesOut.eq(expectedSharesOut), "shares out should match the preview");
+
+    const buyerStatsAccount = await program.account.userStats.fetch(buyerStats);
+    assert.ok(buyerStatsAccount.purchased.eq(sharesOut), "purchased shares");
+  });
+
+  it("should revert for a permanent delegate mint", async () => {
+    const assetMint = await createToken2022Mint(decimals, { permanentDelegate: provider.wallet.publicKey });
+
+    try {
+      await createToken2022Pool(assetMint);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unsupported mint extension");
+    }
+  });
+
+  it("should revert for a non-transferable mint", async () => {
+    const assetMint = await createToken2022Mint(decimals, { nonTransferable: true });
+
+    try {
+      await createToken2022Pool(assetMint);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unsupported mint extension");
+    }
+  });
+});
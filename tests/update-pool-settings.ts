@@ -0,0 +1,197 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert, expect } from "chai";
+import {
+  BN_1,
+  ONE_DAY,
+  SOL,
+  TWO_DAYS,
+  createMintAndVault,
+  createPool,
+  createUser,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+} from "./utils";
+
+describe("Update Pool Settings", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(1300);
+  const decimals = 6; // mint decimals
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const feeRecipient = anchor.web3.Keypair.generate();
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  const createUpcomingPool = async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleStart = now.add(ONE_DAY);
+    poolSettings.saleEnd = now.add(TWO_DAYS);
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    return { poolSettings, ...created };
+  };
+
+  const updatePoolSettings = async (pool, assetVault, settings, authority = depositor) => {
+    let updateEvent = null;
+    const id = program.addEventListener("PoolSettingsUpdated", (event, slot) => {
+      updateEvent = event;
+    });
+
+    try {
+      await program.methods.updatePoolSettings(settings)
+        .accounts({
+          pool: pool.publicKey,
+          assetVault: assetVault.publicKey,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    } finally {
+      program.removeEventListener(id);
+    }
+    return updateEvent;
+  };
+
+  it("should let the owner update settings before sale start", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+
+    const newSettings = { ...poolSettings };
+    newSettings.maxSharePrice = poolSettings.maxSharePrice.mul(new anchor.BN(2));
+    newSettings.weightEnd = SOL.div(new anchor.BN(10)); // 10%
+    newSettings.saleEnd = poolSettings.saleEnd.add(ONE_DAY);
+
+    const updateEvent = await updatePoolSettings(pool, assetVault, newSettings);
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.maxSharePrice.eq(newSettings.maxSharePrice), "max share price");
+    assert.ok(poolAccount.settings.weightEnd.eq(newSettings.weightEnd), "weight end");
+    assert.ok(poolAccount.settings.saleEnd.eq(newSettings.saleEnd), "sale end");
+
+    assert.ok(updateEvent, "event emitted");
+    assert.ok(updateEvent.oldSettings.weightEnd.eq(poolSettings.weightEnd), "old weight end");
+    assert.ok(updateEvent.newSettings.weightEnd.eq(newSettings.weightEnd), "new weight end");
+  });
+
+  it("should revert when not the owner", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+    const stranger = anchor.web3.Keypair.generate();
+    await fund(stranger.publicKey);
+
+    try {
+      await updatePoolSettings(pool, assetVault, poolSettings, stranger);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Unauthorized");
+    }
+  });
+
+  it("should revert after sale start", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const { pool, assetVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    try {
+      await updatePoolSettings(pool, assetVault, poolSettings);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Settings update disallowed");
+    }
+  });
+
+  it("should revert when moving sale start to the past", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+    const now = await getNow();
+
+    const startedSettings = { ...poolSettings, saleStart: now.sub(ONE_DAY) };
+    try {
+      await updatePoolSettings(pool, assetVault, startedSettings);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Settings update disallowed");
+    }
+
+    const poolAccount = await program.account.pool.fetch(pool.publicKey);
+    assert.ok(poolAccount.settings.saleStart.eq(poolSettings.saleStart), "sale start unchanged");
+  });
+
+  it("should re-run the create pool validation", async () => {
+    const { pool, assetVault, poolSettings } = await createUpcomingPool();
+
+    const invalidVest = { ...poolSettings, vestCliff: poolSettings.saleEnd.sub(BN_1), vestEnd: poolSettings.saleEnd.add(BN_1) };
+    try {
+      await updatePoolSettings(pool, assetVault, invalidVest);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Vest Cliff");
+    }
+
+    const invalidWeight = { ...poolSettings, weightEnd: SOL };
+    try {
+      await updatePoolSettings(pool, assetVault, invalidWeight);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Weight Config");
+    }
+
+    const otherAsset = { ...poolSettings, asset: anchor.web3.Keypair.generate().publicKey };
+    try {
+      await updatePoolSettings(pool, assetVault, otherAsset);
+      expect.fail("Should have thrown an error");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Invalid Asset Or Share");
+    }
+  });
+});
@@ -4,9 +4,20 @@ import { Program } from "@coral-xyz/anchor";
 import { LiquidityBootstrapFjord } from "../target/types/liquidity_bootstrap_fjord";
 import { assert, expect, use } from "chai";
 import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { createHash } from "crypto";
+import { BankrunProvider } from "anchor-bankrun";
+import { addLamports, getClockTimestamp, setClockTimestamp, startBankrun } from "./bankrun";
 
-export const program = anchor.workspace.LiquidityBootstrapFjord as Program<LiquidityBootstrapFjord>;
-export const provider = anchor.AnchorProvider.env();
+export let provider: BankrunProvider;
+export let program: Program<LiquidityBootstrapFjord>;
+
+// The program runs in bankrun so tests can set the clock, the bank starts once before any suite
+// and the provider has to be set before the workspace builds the program
+before(async () => {
+    provider = await startBankrun();
+    anchor.setProvider(provider);
+    program = anchor.workspace.LiquidityBootstrapFjord as Program<LiquidityBootstrapFjord>;
+});
 
 /* constants */
 
@@ -14,6 +25,7 @@ export const SOL = new anchor.BN(1_000_000_000);
 export const ONE_DAY = new anchor.BN(86400);
 export const TWO_DAYS = new anchor.BN(172800);
 export const TEN_DAYS = new anchor.BN(864000);
+export const SALE_WINDOW = new anchor.BN(10); // seconds a test pool stays open when the test has to close it
 export const BN_0 = new anchor.BN(0);
 export const BN_1 = new anchor.BN(1);
 export const defaultInitialShareAmount = SOL.mul(new anchor.BN(1000));
@@ -71,8 +83,104 @@ export const createMintAndVault = async(
     return [mint.publicKey, vault.publicKey];
 }
 
+export const createToken2022Mint = async (
+    decimals: number,
+    extensions: {
+        transferFeeBasisPoints?: number,
+        permanentDelegate?: anchor.web3.PublicKey,
+        nonTransferable?: boolean,
+    } = {}
+) => {
+    const mint = new anchor.web3.Keypair();
+    const programId = splToken.TOKEN_2022_PROGRAM_ID;
+    const extensionTypes = [];
+    const extensionInstrs = [];
+
+    if (extensions.transferFeeBasisPoints !== undefined) {
+        extensionTypes.push(splToken.ExtensionType.TransferFeeConfig);
+        extensionInstrs.push(splToken.createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            provider.wallet.publicKey,
+            provider.wallet.publicKey,
+            extensions.transferFeeBasisPoints,
+            BigInt("18446744073709551615"),
+            programId
+        ));
+    }
+    if (extensions.permanentDelegate) {
+        extensionTypes.push(splToken.ExtensionType.PermanentDelegate);
+        extensionInstrs.push(splToken.createInitializePermanentDelegateInstruction(
+            mint.publicKey,
+            extensions.permanentDelegate,
+            programId
+        ));
+    }
+    if (extensions.nonTransferable) {
+        extensionTypes.push(splToken.ExtensionType.NonTransferable);
+        extensionInstrs.push(splToken.createInitializeNonTransferableMintInstruction(
+            mint.publicKey,
+            programId
+        ));
+    }
+
+    const space = splToken.getMintLen(extensionTypes);
+    const tx = new anchor.web3.Transaction();
+    tx.add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+          programId,
+        }),
+        ...extensionInstrs,
+        splToken.createInitializeMintInstruction(
+          mint.publicKey,
+          decimals,
+          provider.wallet.publicKey,
+          null,
+          programId
+        ),
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+    return mint.publicKey;
+}
+
+export const createToken2022Vault = async (
+    mint: anchor.web3.PublicKey,
+    owner: anchor.web3.PublicKey,
+    amount: anchor.BN = BN_0
+) => {
+    const programId = splToken.TOKEN_2022_PROGRAM_ID;
+    const vault = splToken.getAssociatedTokenAddressSync(mint, owner, false, programId);
+    const tx = new anchor.web3.Transaction();
+    tx.add(
+        splToken.createAssociatedTokenAccountIdempotentInstruction(
+            provider.wallet.publicKey,
+            vault,
+            owner,
+            mint,
+            programId
+        )
+    );
+    if (!amount.isZero()) {
+        tx.add(
+            splToken.createMintToInstruction(
+                mint,
+                vault,
+                provider.wallet.publicKey,
+                BigInt(amount.toString()),
+                [],
+                programId
+            )
+        );
+    }
+    await provider.sendAndConfirm(tx, []);
+    return vault;
+}
+
 export async function createTokenAccountInstrs(
-    provider: anchor.AnchorProvider,
+    provider: BankrunProvider,
     newAccountPubkey: anchor.web3.PublicKey,
     mint: anchor.web3.PublicKey,
     owner: anchor.web3.PublicKey,
@@ -95,7 +203,7 @@ export async function createTokenAccountInstrs(
 }
 
 export async function createTokenAccount(
-    provider: anchor.AnchorProvider,
+    provider: BankrunProvider,
     mint: anchor.web3.PublicKey,
     owner: anchor.web3.PublicKey
 ): Promise<anchor.web3.PublicKey> {
@@ -109,16 +217,7 @@ export async function createTokenAccount(
 }
 
 export const fund = async (pubkey) => {
-    const airdropSignature = await provider.connection.requestAirdrop(
-      pubkey,
-      1000 * SOL.toNumber()
-    );
-    const latestBlockHash = await provider.connection.getLatestBlockhash();
-    await provider.connection.confirmTransaction({
-      blockhash: latestBlockHash.blockhash,
-      lastValidBlockHeight: latestBlockHash.lastValidBlockHeight,
-      signature: airdropSignature,
-    });
+    await addLamports(pubkey, 1000 * SOL.toNumber());
 };
 
 export const createVault = async (
@@ -138,19 +237,24 @@ export const getDefaultPoolSettings = async (
     assetMint: anchor.web3.PublicKey,
     shareMint: anchor.web3.PublicKey,
 ) => {
-    let now = new anchor.BN(
-      await provider.connection.getBlockTime(
-        await provider.connection.getSlot()
-      )
-    );
+    let now = await getNow();
     const weightStart = SOL.div(new anchor.BN(2));
     const weightEnd = SOL.div(new anchor.BN(2));
-    const saleStart = now.add(ONE_DAY);
+    const saleStart = now.sub(ONE_DAY);
     const saleEnd = now.add(TWO_DAYS);
     const sellingAllowed = true;
+    const extendSaleOnPause = false;
+    const whitelistMerkleRoot = null;
+    const maxSharesPerUser = null;
+    const maxAssetsPerUser = null;
+    const minAssetsPerBuy = null;
+    const weightCurve = { linear: {} };
+    const vestingSchedule = { initialUnlockBps: 0, release: { linear: {} } };
     const maxSharePrice = new anchor.BN(SOL.mul(new anchor.BN(10_000)));
     const maxSharesOut = new anchor.BN(SOL.mul(new anchor.BN(1000_000_000)));
     const maxAssetsIn = new anchor.BN(SOL.mul(new anchor.BN(1000_000_000)));
+    const maxPercentageIn = SOL.muln(3).divn(10); // 30% of the reserve per swap
+    const maxPercentageOut = SOL.muln(3).divn(10);
     const vestCliff = now.add(TEN_DAYS); // 10 days later
     const vestEnd = now.add(TEN_DAYS.mul(new anchor.BN(2))); // 20 days later
     const virtualAssets = BN_0;
@@ -163,6 +267,8 @@ export const getDefaultPoolSettings = async (
       maxSharePrice,
       maxSharesOut,
       maxAssetsIn,
+      maxPercentageIn,
+      maxPercentageOut,
       weightStart,
       weightEnd,
       saleStart,
@@ -170,6 +276,13 @@ export const getDefaultPoolSettings = async (
       vestCliff,
       vestEnd,
       sellingAllowed,
+      extendSaleOnPause,
+      whitelistMerkleRoot,
+      maxSharesPerUser,
+      maxAssetsPerUser,
+      minAssetsPerBuy,
+      weightCurve,
+      vestingSchedule,
     };
     return poolSettings;
 };
@@ -177,7 +290,8 @@ export const getDefaultPoolSettings = async (
 export const initialize = async (
     factoryId: anchor.BN,
     fee_recipient: anchor.web3.PublicKey,
-    lbpFactorySettingsAuthority: anchor.web3.Keypair
+    lbpFactorySettingsAuthority: anchor.web3.Keypair,
+    closeGracePeriod: anchor.BN = ONE_DAY
 ) => {
     const [lbpFactoryPda] = anchor.web3.PublicKey.findProgramAddressSync(
         [
@@ -194,7 +308,8 @@ export const initialize = async (
         fee_recipient,
         new anchor.BN(1000),
         new anchor.BN(1000),
-        new anchor.BN(1000)
+        new anchor.BN(1000),
+        closeGracePeriod
     )
     .accounts({
         authority: lbpFactorySettingsAuthority.publicKey,
@@ -206,6 +321,21 @@ export const initialize = async (
     return lbpFactoryPda;
 }
 
+export const getPoolPda = (
+    lbpFactoryPda: anchor.web3.PublicKey,
+    poolId: anchor.BN
+) => {
+    const [poolPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("pool"),
+          lbpFactoryPda.toBuffer(),
+          poolId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+    );
+    return poolPda;
+}
+
 export const createPool = async (
     poolId: anchor.BN,
     poolSettings: any,
@@ -219,38 +349,32 @@ export const createPool = async (
     initialAssetAmount: anchor.BN = defaultInitialAssetAmount,
 ) => {
 
-    const pool = anchor.web3.Keypair.generate();
-    const assetVault = anchor.web3.Keypair.generate();
-    const shareVault = anchor.web3.Keypair.generate();
-
-    const [assetVaultAuthority, assetVaultNonce] =
+    // pools live at a PDA derived from the factory and the pool id
+    const pool = { publicKey: getPoolPda(lbpFactoryPda, poolId) };
+    const [assetVaultAuthority] =
         anchor.web3.PublicKey.findProgramAddressSync(
             [anchor.utils.bytes.utf8.encode("asset"), pool.publicKey.toBuffer()],
             program.programId
         );
-    const [shareVaultAuthority, shareVaultNonce] =
+    const [shareVaultAuthority] =
         anchor.web3.PublicKey.findProgramAddressSync(
             [anchor.utils.bytes.utf8.encode("share"), pool.publicKey.toBuffer()],
             program.programId
     );
 
-    const tx = new anchor.web3.Transaction();
-    tx.add(
-        await program.account.pool.createInstruction(pool),
-        ...(await createTokenAccountInstrs(
-            provider,
-            assetVault.publicKey,
-            assetMint,
-            assetVaultAuthority
-        )),
-        ...(await createTokenAccountInstrs(
-            provider,
-            shareVault.publicKey,
-            shareMint,
-            shareVaultAuthority
-        )),
-    )
-    await provider.sendAndConfirm(tx, [pool, assetVault, shareVault]);
+    // vaults are created by the program at PDAs owned by the vault authorities
+    const [assetVaultPda] =
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [anchor.utils.bytes.utf8.encode("asset_vault"), pool.publicKey.toBuffer()],
+            program.programId
+        );
+    const [shareVaultPda] =
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [anchor.utils.bytes.utf8.encode("share_vault"), pool.publicKey.toBuffer()],
+            program.programId
+    );
+    const assetVault = { publicKey: assetVaultPda };
+    const shareVault = { publicKey: shareVaultPda };
 
     await program.methods
         .createPool(
@@ -258,18 +382,21 @@ export const createPool = async (
             poolId, 
             initialShareAmount, 
             initialAssetAmount,
-            shareVaultNonce,
-            assetVaultNonce,
         )
         .accounts({
             pool: pool.publicKey,
+            assetMint: assetMint,
+            shareMint: shareMint,
+            assetVaultAuthority: assetVaultAuthority,
+            shareVaultAuthority: shareVaultAuthority,
             assetVault: assetVault.publicKey,
             shareVault: shareVault.publicKey,
             depositorAssetVault: depositorAssetVault,
             depositorShareVault: depositorShareVault,
             depositor: depositor.publicKey,
             lbpFactorySetting: lbpFactoryPda,
-            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            assetTokenProgram: await getTokenProgramId(assetMint),
+            shareTokenProgram: await getTokenProgramId(shareMint),
             rent: SYSVAR_RENT_PUBKEY,
             systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -292,6 +419,11 @@ export const getSwapFees = async (lbpFactoryPda) => {
 
 /* Pool Methods */
 
+export const getTokenProgramId = async (account: anchor.web3.PublicKey) => {
+    const accountInfo = await provider.connection.getAccountInfo(account);
+    return accountInfo.owner;
+}
+
 export const closePool = async (
     pool, 
     assetVault, 
@@ -302,10 +434,15 @@ export const closePool = async (
     poolOwnerShareVault,
     feeRecipientShareVault,
     feeRecipientAssetVault,
-    lbpFactoryPda
+    lbpFactoryPda,
+    closer: anchor.web3.Keypair
 ) => {
-    
+    const poolAccount = await program.account.pool.fetch(pool);
+    const assetMint = poolAccount.settings.asset;
+    const shareMint = poolAccount.settings.share;
+
     await program.methods.close().accounts({
+        closer: closer.publicKey,
         pool: pool,
         assetVault,
         assetVaultAuthority,
@@ -316,10 +453,13 @@ export const closePool = async (
         feeRecipientAssetVault,
         feeRecipientShareVault,
         lbpFactorySetting:lbpFactoryPda,
-        
-        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        assetMint,
+        shareMint,
+        assetTokenProgram: await getTokenProgramId(assetMint),
+        shareTokenProgram: await getTokenProgramId(shareMint),
         systemProgram: anchor.web3.SystemProgram.programId,
     })
+    .signers([closer])
     .rpc()
 };
 
@@ -334,7 +474,8 @@ export const swapExactAssetsForShares = async (
     buyerStats,
     assetMint,
     shareMint,
-
+    referrer: anchor.web3.PublicKey = null,
+    allowlist = null,
 ) => {
     let buyEvent = null;
     const id = program.addEventListener("Buy", (event, slot) => {
@@ -342,7 +483,7 @@ export const swapExactAssetsForShares = async (
     });
 
     await program.methods
-      .swapExactAssetsForShares(buyer.publicKey, assetsIn, BN_0)
+      .swapExactAssetsForShares(buyer.publicKey, assetsIn, BN_0, referrer, allowlist)
       .accounts({
         depositor: buyer.publicKey,
         pool: pool.publicKey,
@@ -354,8 +495,9 @@ export const swapExactAssetsForShares = async (
         depositorAssetVault: depositorAssetVault,
         // depositorAssetVaultAuthority,
         recipientUserStats: buyerStats,
+        referrerStats: referrer ? await getReferrerStatsPda(pool.publicKey, referrer) : null,
 
-        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        tokenProgram: await getTokenProgramId(assetMint),
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -436,6 +578,41 @@ export const createUserStats = async (
     }
 }
 
+export const getReferrerStatsPda = async (
+    pool: anchor.web3.PublicKey,
+    referrer: anchor.web3.PublicKey
+) => {
+    const [referrerStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          anchor.utils.bytes.utf8.encode("referrer_stats"),
+          pool.toBuffer(),
+          referrer.toBuffer(),
+        ],
+        program.programId
+    );
+    return referrerStatsPda;
+}
+
+export const createReferrerStats = async (
+    pool: anchor.web3.PublicKey,
+    referrer: anchor.web3.PublicKey
+) => {
+    const referrerStatsPda = await getReferrerStatsPda(pool, referrer);
+
+    await program.methods.createReferrerStats(
+    ).accounts({
+        payer: provider.wallet.publicKey,
+        referrer: referrer,
+        pool: pool,
+        referrerStats: referrerStatsPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+    }).rpc();
+
+    return {
+        referrerStats: referrerStatsPda
+    }
+}
+
 export const createUser = async (
     assetMint: anchor.web3.PublicKey,
     shareMint: anchor.web3.PublicKey,
@@ -481,13 +658,23 @@ export const createUser = async (
 
 /* Test Setup Helpers */
 
+// The clock only moves when a test warps it, so every instruction runs at a known timestamp
 export const getNow = async () => {
-    const now = new anchor.BN(
-        await provider.connection.getBlockTime(
-          await provider.connection.getSlot()
-        )
-    )   
-    return now;
+    return await getClockTimestamp();
+};
+
+export const warpTo = async (timestamp: anchor.BN) => {
+    await setClockTimestamp(timestamp);
+};
+
+// Runs `fn` and expects it to fail with the program error `errorMessage`
+export const expectError = async (fn: () => Promise<any>, errorMessage: string, message: string) => {
+    try {
+        await fn();
+        expect.fail(message);
+    } catch (error) {
+        expect(error.error.errorMessage).to.equal(errorMessage);
+    }
 };
 
 export const getVaultBalance = async (
@@ -495,7 +682,50 @@ export const getVaultBalance = async (
 ) => {
     const accountInfo = await splToken.getAccount(
         provider.connection,
-        vault
+        vault,
+        undefined,
+        await getTokenProgramId(vault)
     );
     return new anchor.BN(accountInfo.amount.toString());
-}
\ No newline at end of file
+}
+
+/* Allowlist Utils */
+
+const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+
+// Mirrors `allowlist_leaf` on chain
+export const getAllowlistLeaf = (recipient: anchor.web3.PublicKey, maxAllocation: anchor.BN) =>
+    sha256(recipient.toBuffer(), maxAllocation.toArrayLike(Buffer, "le", 8));
+
+// Builds a sorted pair merkle tree, returns the root and one proof per entry
+export const buildAllowlist = (entries: { recipient: anchor.web3.PublicKey, maxAllocation: anchor.BN }[]) => {
+    const leaves = entries.map(({ recipient, maxAllocation }) => getAllowlistLeaf(recipient, maxAllocation));
+    const layers: Buffer[][] = [leaves];
+    while (layers[layers.length - 1].length > 1) {
+        const layer = layers[layers.length - 1];
+        const next: Buffer[] = [];
+        for (let i = 0; i < layer.length; i += 2) {
+            if (i + 1 == layer.length) {
+                next.push(layer[i]);
+            } else {
+                const [a, b] = Buffer.compare(layer[i], layer[i + 1]) <= 0 ? [layer[i], layer[i + 1]] : [layer[i + 1], layer[i]];
+                next.push(sha256(a, b));
+            }
+        }
+        layers.push(next);
+    }
+
+    const proofs = entries.map((entry, index) => {
+        const proof: number[][] = [];
+        for (const layer of layers.slice(0, -1)) {
+            const sibling = index ^ 1;
+            if (sibling < layer.length) {
+                proof.push(Array.from(layer[sibling]));
+            }
+            index = Math.floor(index / 2);
+        }
+        return { maxAllocation: entry.maxAllocation, proof };
+    });
+
+    return { root: Array.from(layers[layers.length - 1][0]), proofs };
+};
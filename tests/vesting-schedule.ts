@@ -0,0 +1,396 @@
+import * as anchor from "@coral-xyz/anchor";
+import * as splToken from "@solana/spl-token";
+import { assert, expect } from "chai";
+import { SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  SALE_WINDOW,
+  SOL,
+  closePool,
+  createMintAndVault,
+  createPool,
+  createUser,
+  createUserStats,
+  defaultInitialAssetAmount,
+  defaultInitialShareAmount,
+  fund,
+  getDefaultPoolSettings,
+  getNow,
+  initialize,
+  program,
+  provider,
+  swapExactAssetsForShares,
+  warpTo,
+} from "./utils";
+
+describe("Vesting Schedules", () => {
+  /* Settings */
+  const factoryId = new anchor.BN(2300);
+  const decimals = 6; // mint decimals
+  const STEP = new anchor.BN(10); // seconds between unlocks
+
+  /* Global Variables */
+  let assetMint;
+  let shareMint;
+
+  let buyer;
+  let buyerAssetVault;
+  let buyerShareVault;
+
+  let depositor;
+  let depositorAssetVault;
+  let depositorShareVault;
+
+  let feeAssetVault;
+  let feeShareVault;
+
+  let lbpFactoryPda;
+
+  let poolId = factoryId.clone();
+
+  before(async () => {
+    // funds users
+    await fund(provider.wallet.publicKey);
+
+    // prepare mints
+    [assetMint] = await createMintAndVault(
+      defaultInitialAssetAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+    [shareMint] = await createMintAndVault(
+      defaultInitialShareAmount,
+      provider.wallet.publicKey,
+      decimals
+    );
+
+    // prepare factory settings authority
+    const lbpFactorySettingsAuthority = anchor.web3.Keypair.generate();
+    await fund(lbpFactorySettingsAuthority.publicKey);
+
+    // prepare fee recipient
+    const {
+      user: feeRecipient,
+      userAssetVault: _feeAssetVault,
+      userShareVault: _feeShareVault,
+    } = await createUser(assetMint, shareMint);
+    feeAssetVault = _feeAssetVault;
+    feeShareVault = _feeShareVault;
+
+    // init manager
+    lbpFactoryPda = await initialize(factoryId, feeRecipient.publicKey, lbpFactorySettingsAuthority);
+  });
+
+  beforeEach(async () => {
+    // use a new pool id
+    poolId = poolId.add(new anchor.BN(1));
+
+    const {
+      user: _buyer,
+      userAssetVault: _buyerAssetVault,
+      userShareVault: _buyerShareVault,
+    } = await createUser(assetMint, shareMint);
+    buyer = _buyer;
+    buyerAssetVault = _buyerAssetVault;
+    buyerShareVault = _buyerShareVault;
+
+    const {
+      user: _depositor,
+      userAssetVault: _depositorAssetVault,
+      userShareVault: _depositorShareVault
+    } = await createUser(assetMint, shareMint);
+    depositor = _depositor;
+    depositorAssetVault = _depositorAssetVault;
+    depositorShareVault = _depositorShareVault;
+  });
+
+  // vesting starts one step after the sale and lasts `steps` steps
+  const getVestingSettings = async (vestingSchedule, steps = 2) => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const now = await getNow();
+    poolSettings.saleEnd = now.add(SALE_WINDOW);
+    poolSettings.vestCliff = poolSettings.saleEnd.add(STEP);
+    poolSettings.vestEnd = poolSettings.vestCliff.add(STEP.muln(steps));
+    poolSettings.vestingSchedule = vestingSchedule;
+    return poolSettings;
+  };
+
+  // buys, then closes the pool once the sale ended
+  const buyAndClose = async (poolSettings) => {
+    const created = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+    const { pool, assetVault, assetVaultAuthority, shareVault, shareVaultAuthority } = created;
+    const { userStats: buyerStats } = await createUserStats(pool.publicKey, buyer);
+    const { sharesOut } = await swapExactAssetsForShares(
+      SOL,
+      pool,
+      buyer,
+      shareVault.publicKey,
+      assetVault.publicKey,
+      buyerAssetVault,
+      lbpFactoryPda,
+      buyerStats,
+      assetMint,
+      shareMint
+    );
+
+    await warpTo(poolSettings.saleEnd);
+    await closePool(
+      pool.publicKey,
+      assetVault.publicKey,
+      assetVaultAuthority,
+      shareVault.publicKey,
+      shareVaultAuthority,
+      depositorAssetVault,
+      depositorShareVault,
+      feeShareVault,
+      feeAssetVault,
+      lbpFactoryPda,
+      depositor
+    );
+    return { buyerStats, sharesOut, ...created };
+  };
+
+  // redeems `amount` shares, everything claimable when omitted
+  const redeem = async ({ pool, shareVault, shareVaultAuthority, buyerStats }, amount: anchor.BN | null = null) => {
+    await program.methods
+      .redeem(amount, buyerShareVault)
+      .accounts({
+        user: buyer.publicKey,
+        pool: pool.publicKey,
+        shareVault: shareVault.publicKey,
+        shareVaultAuthority: shareVaultAuthority,
+        lbpFactorySetting: lbpFactoryPda,
+        buyerStats: buyerStats,
+        recipientShareVault: buyerShareVault,
+        shareMint: shareMint,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    return (await program.account.userStats.fetch(buyerStats)).claimed;
+  };
+
+  const previewRedeem = ({ pool, buyerStats }) =>
+    program.methods
+      .previewRedeem()
+      .accounts({
+        pool: pool.publicKey,
+        user: buyer.publicKey,
+        userStats: buyerStats,
+      })
+      .view();
+
+  const expectNothingToClaim = async (created, message: string) => {
+    try {
+      await redeem(created);
+      expect.fail(message);
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("No Shares To Claim");
+    }
+  };
+
+  const bpsOf = (amount: anchor.BN, bps: number) => amount.muln(bps).divn(10_000);
+
+  it("should validate vesting schedules on pool creation", async () => {
+    const invalidSchedules = [
+      { name: "initial unlock above 100%", schedule: { initialUnlockBps: 10_001, release: { linear: {} } } },
+      { name: "zero period", schedule: { initialUnlockBps: 0, release: { periodic: { period: new anchor.BN(0) } } } },
+      { name: "no tranches", schedule: { initialUnlockBps: 0, release: { tranches: { tranches: [] } } } },
+      {
+        name: "tranches not adding up to 100%",
+        schedule: { initialUnlockBps: 1_000, release: { tranches: { tranches: [{ offset: new anchor.BN(0), bps: 8_000 }] } } },
+      },
+      {
+        name: "tranche offsets out of order",
+        schedule: { initialUnlockBps: 0, release: { tranches: { tranches: [{ offset: STEP, bps: 5_000 }, { offset: STEP, bps: 5_000 }] } } },
+      },
+      {
+        name: "tranche after vest end",
+        schedule: { initialUnlockBps: 0, release: { tranches: { tranches: [{ offset: STEP.muln(3), bps: 10_000 }] } } },
+      },
+    ];
+
+    for (const { name, schedule } of invalidSchedules) {
+      const poolSettings = await getVestingSettings(schedule);
+      try {
+        await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+        expect.fail(`${name} should be rejected`);
+      } catch (error) {
+        expect(error.error.errorMessage).to.equal("Invalid Vesting Schedule", name);
+      }
+    }
+  });
+
+  it("should unlock the initial amount and tranches on schedule", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 2_000,
+      release: {
+        tranches: {
+          tranches: [
+            { offset: new anchor.BN(0), bps: 3_000 },
+            { offset: STEP, bps: 5_000 },
+          ],
+        },
+      },
+    });
+    const created = await buyAndClose(poolSettings);
+    const { sharesOut } = created;
+
+    // before the cliff only the initial unlock is claimable
+    assert.ok((await redeem(created)).eq(bpsOf(sharesOut, 2_000)), "initial unlock");
+    await expectNothingToClaim(created, "nothing more before the cliff");
+
+    await warpTo(poolSettings.vestCliff);
+    assert.ok((await redeem(created)).eq(bpsOf(sharesOut, 5_000)), "first tranche");
+
+    await warpTo(poolSettings.vestCliff.add(STEP));
+    assert.ok((await redeem(created)).eq(sharesOut), "second tranche");
+  });
+
+  it("should unlock periodically after the cliff", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 0,
+      release: { periodic: { period: STEP } },
+    });
+    const created = await buyAndClose(poolSettings);
+    const { sharesOut } = created;
+
+    await expectNothingToClaim(created, "nothing before the first period");
+
+    await warpTo(poolSettings.vestCliff.add(STEP));
+    assert.ok((await redeem(created)).eq(sharesOut.divn(2)), "first period");
+    await expectNothingToClaim(created, "nothing between periods");
+
+    await warpTo(poolSettings.vestEnd);
+    assert.ok((await redeem(created)).eq(sharesOut), "everything at vest end");
+  });
+
+  it("should vest linearly after the initial unlock", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 5_000,
+      release: { linear: {} },
+    });
+    const created = await buyAndClose(poolSettings);
+    const { sharesOut } = created;
+
+    assert.ok((await redeem(created)).eq(bpsOf(sharesOut, 5_000)), "initial unlock");
+
+    await warpTo(poolSettings.vestCliff.add(STEP));
+    const initial = bpsOf(sharesOut, 5_000);
+    assert.ok((await redeem(created)).eq(initial.add(sharesOut.sub(initial).divn(2))), "half of the rest vested");
+
+    await warpTo(poolSettings.vestEnd);
+    assert.ok((await redeem(created)).eq(sharesOut), "everything at vest end");
+  });
+
+  it("should redeem part of the claimable shares at the cliff, mid-vest and after vest end", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 2_000,
+      release: { periodic: { period: STEP } },
+    });
+    const created = await buyAndClose(poolSettings);
+    const { sharesOut } = created;
+    const initial = bpsOf(sharesOut, 2_000);
+
+    // at the cliff only the initial unlock is claimable, take half of it
+    await warpTo(poolSettings.vestCliff);
+    assert.ok((await redeem(created, initial.divn(2))).eq(initial.divn(2)), "half of the initial unlock");
+    try {
+      await redeem(created, initial);
+      expect.fail("redeeming more than claimable should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("Redeem Amount Exceeds Claimable");
+    }
+    assert.ok((await redeem(created)).eq(initial), "rest of the initial unlock");
+
+    // mid-vest half of the remaining shares unlocked
+    await warpTo(poolSettings.vestCliff.add(STEP));
+    const midVest = initial.add(sharesOut.sub(initial).divn(2));
+    assert.ok((await redeem(created, new anchor.BN(1))).eq(initial.addn(1)), "a single share");
+    assert.ok((await redeem(created)).eq(midVest), "everything vested mid-vest");
+    await expectNothingToClaim(created, "nothing more mid-vest");
+
+    // after vest end everything is claimable
+    await warpTo(poolSettings.vestEnd.add(STEP));
+    const remaining = sharesOut.sub(midVest);
+    assert.ok((await redeem(created, remaining.subn(1))).eq(sharesOut.subn(1)), "all but one share");
+    assert.ok((await redeem(created)).eq(sharesOut), "last share");
+    await expectNothingToClaim(created, "nothing left after vest end");
+  });
+
+  it("should reject redeeming zero shares", async () => {
+    const poolSettings = await getVestingSettings({ initialUnlockBps: 10_000, release: { linear: {} } });
+    const created = await buyAndClose(poolSettings);
+
+    try {
+      await redeem(created, new anchor.BN(0));
+      expect.fail("redeeming zero shares should fail");
+    } catch (error) {
+      expect(error.error.errorMessage).to.equal("No Shares To Claim");
+    }
+    assert.ok((await redeem(created)).eq(created.sharesOut), "everything unlocked up front");
+  });
+
+  it("should preview what redeem pays out", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 2_000,
+      release: { periodic: { period: STEP } },
+    });
+    const created = await buyAndClose(poolSettings);
+    const { sharesOut } = created;
+    const initial = bpsOf(sharesOut, 2_000);
+
+    // before the cliff the initial unlock is claimable and the first period is next
+    let preview = await previewRedeem(created);
+    assert.ok(preview.claimable.eq(initial), "initial unlock claimable");
+    assert.ok(preview.vested.eq(initial), "initial unlock vested");
+    assert.ok(preview.claimed.eqn(0), "nothing claimed");
+    assert.ok(preview.nextUnlockAt.eq(poolSettings.vestCliff.add(STEP)), "first period next");
+
+    assert.ok((await redeem(created)).eq(preview.claimable), "redeem pays the preview");
+    preview = await previewRedeem(created);
+    assert.ok(preview.claimable.eqn(0), "nothing left to claim");
+    assert.ok(preview.claimed.eq(initial), "initial unlock claimed");
+
+    // mid-vest the last period ends at vest end
+    await warpTo(poolSettings.vestCliff.add(STEP));
+    preview = await previewRedeem(created);
+    const midVest = initial.add(sharesOut.sub(initial).divn(2));
+    assert.ok(preview.vested.eq(midVest), "half of the rest vested");
+    assert.ok(preview.claimable.eq(midVest.sub(initial)), "claimable excludes claimed shares");
+    assert.ok(preview.nextUnlockAt.eq(poolSettings.vestEnd), "vest end next");
+
+    // after vest end everything is vested and nothing unlocks anymore
+    await warpTo(poolSettings.vestEnd);
+    preview = await previewRedeem(created);
+    assert.ok(preview.vested.eq(sharesOut), "everything vested");
+    assert.ok(preview.claimable.eq(sharesOut.sub(initial)), "rest claimable");
+    assert.equal(preview.nextUnlockAt, null, "no next unlock");
+    assert.ok((await redeem(created)).eq(sharesOut), "redeem pays the preview");
+  });
+
+  it("should preview the next tranche", async () => {
+    const poolSettings = await getVestingSettings({
+      initialUnlockBps: 0,
+      release: {
+        tranches: {
+          tranches: [
+            { offset: new anchor.BN(0), bps: 4_000 },
+            { offset: STEP, bps: 6_000 },
+          ],
+        },
+      },
+    });
+    const created = await buyAndClose(poolSettings);
+
+    let preview = await previewRedeem(created);
+    assert.ok(preview.claimable.eqn(0), "nothing before the cliff");
+    assert.ok(preview.nextUnlockAt.eq(poolSettings.vestCliff), "first tranche at the cliff");
+
+    await warpTo(poolSettings.vestCliff);
+    preview = await previewRedeem(created);
+    assert.ok(preview.claimable.eq(bpsOf(created.sharesOut, 4_000)), "first tranche claimable");
+    assert.ok(preview.nextUnlockAt.eq(poolSettings.vestCliff.add(STEP)), "second tranche next");
+  });
+});
@@ -1,6 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { assert, expect } from "chai";
-import { ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, initialize, program, provider, swapExactAssetsForShares } from "./utils";
+import { BN_0, ONE_DAY, SOL, closePool, createMintAndVault, createPool, createUser, createUserStats, createVault, defaultInitialAssetAmount, defaultInitialShareAmount, fund, getDefaultPoolSettings, getNow, initialize, program, provider, swapExactAssetsForShares } from "./utils";
 
 describe("Weight Calculations", () => {
   /* Settings */
@@ -209,4 +209,82 @@ describe("Weight Calculations", () => {
     
     assert.ok(output.assetReserve.toString() === "1000000000000", "assetReserve");
   });
+
+  const computeWeights = async (pool, assetVault, shareVault) => {
+    return await program.methods.computeReservesAndWeights()
+      .accounts({
+        pool: pool.publicKey,
+        poolAssetVault: assetVault.publicKey,
+        poolShareVault: shareVault.publicKey,
+      })
+      .view();
+  };
+
+  it("test step weight curve", async () => {
+    // one day into a three day sale, still on the first of two steps
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.weightStart = SOL.div(new anchor.BN(10)).mul(new anchor.BN(9)); // 90%
+    poolSettings.weightEnd = SOL.div(new anchor.BN(10)); // 10%
+    poolSettings.weightCurve = { step: { steps: 2 } };
+
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const output = await computeWeights(pool, assetVault, shareVault);
+    assert.ok(output.assetWeight.eq(poolSettings.weightStart), "asset weight should stay at the start weight");
+    assert.ok(output.weightCurve.step.steps === 2, "weight curve should be returned");
+  });
+
+  it("test exponential decay weight curve", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.weightStart = SOL.div(new anchor.BN(10)).mul(new anchor.BN(9)); // 90%
+    poolSettings.weightEnd = SOL.div(new anchor.BN(10)); // 10%
+    poolSettings.weightCurve = { exponentialDecay: { decayRate: SOL.mul(new anchor.BN(5)) } };
+
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    // a third into the sale (1 - e^(-5/3)) / (1 - e^(-5)) ~ 81.7% of the change is done,
+    // the asset weight is around 24.7% where the linear curve would be around 63%
+    const output = await computeWeights(pool, assetVault, shareVault);
+    assert.ok(output.assetWeight.gt(SOL.div(new anchor.BN(100)).mul(new anchor.BN(23))), "asset weight lower bound");
+    assert.ok(output.assetWeight.lt(SOL.div(new anchor.BN(100)).mul(new anchor.BN(26))), "asset weight upper bound");
+  });
+
+  it("test piecewise linear weight curve", async () => {
+    // half a day into the sale the curve reaches 20% and stays there until the end
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    poolSettings.weightStart = SOL.div(new anchor.BN(10)).mul(new anchor.BN(9)); // 90%
+    poolSettings.weightEnd = SOL.div(new anchor.BN(10)).mul(new anchor.BN(2)); // 20%
+    poolSettings.weightCurve = {
+      piecewiseLinear: {
+        breakpoints: [{ offset: ONE_DAY.div(new anchor.BN(2)), weight: poolSettings.weightEnd }],
+      },
+    };
+
+    const { pool, assetVault, shareVault } = await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+
+    const output = await computeWeights(pool, assetVault, shareVault);
+    assert.ok(output.assetWeight.eq(poolSettings.weightEnd), "asset weight should be on the flat segment");
+  });
+
+  it("should revert invalid weight curves", async () => {
+    const poolSettings = await getDefaultPoolSettings(assetMint, shareMint);
+    const saleDuration = poolSettings.saleEnd.sub(poolSettings.saleStart);
+    const invalidCurves = [
+      { step: { steps: 1 } },
+      { exponentialDecay: { decayRate: BN_0 } },
+      { piecewiseLinear: { breakpoints: [] } },
+      { piecewiseLinear: { breakpoints: [{ offset: saleDuration, weight: poolSettings.weightEnd }] } },
+      { piecewiseLinear: { breakpoints: [{ offset: ONE_DAY, weight: SOL }] } },
+    ];
+
+    for (const weightCurve of invalidCurves) {
+      poolSettings.weightCurve = weightCurve;
+      try {
+        await createPool(poolId, poolSettings, depositorAssetVault, depositorShareVault, depositor, lbpFactoryPda, assetMint, shareMint);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.error.errorMessage).to.equal("Invalid Weight Curve");
+      }
+    }
+  });
 });